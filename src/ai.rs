@@ -0,0 +1,199 @@
+// Computer-controlled paddle.
+//
+// The CPU predicts where the ball will cross its paddle's column, following reflections off
+// the top and bottom walls, and steers toward that row. A paddle along the top or bottom
+// row is steered the same way, turned on its side: toward the column where the ball will
+// reach its row. Difficulty comes from how long it takes to react, how far off its
+// prediction may be, and how fast it can move.
+
+use crate::platform::vga_buffer::{BUFFER_WIDTH, BUFFER_HEIGHT};
+use crate::{Ball, Difficulty, Player};
+use crate::fixed::Fixed;
+
+/// Tunable behavior of a CPU opponent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AiProfile {
+    /// Ticks between the ball heading toward the paddle and the CPU starting to respond.
+    pub reaction_delay: usize,
+    /// Largest number of cells by which the predicted intercept may miss the true one.
+    pub prediction_error: usize,
+    /// Cells the paddle may move per tick.
+    pub max_speed: usize,
+}
+
+pub const EASY: AiProfile = AiProfile { reaction_delay: 10, prediction_error: 5, max_speed: 1 };
+pub const MEDIUM: AiProfile = AiProfile { reaction_delay: 5, prediction_error: 2, max_speed: 1 };
+pub const HARD: AiProfile = AiProfile { reaction_delay: 1, prediction_error: 0, max_speed: 2 };
+
+/// Returns the CPU profile for **difficulty**, or None when both paddles are human.
+pub fn profile_for(difficulty: Difficulty) -> Option<AiProfile> {
+    match difficulty {
+        Difficulty::Multiplayer => None,
+        Difficulty::Easy => Some(EASY),
+        Difficulty::Medium => Some(MEDIUM),
+        Difficulty::Hard => Some(HARD),
+    }
+}
+
+/// Returns the row at which **ball** will reach column **target_x**, accounting for bounces
+/// off the top and bottom walls. Returns None if the ball is not moving toward that column.
+pub fn predict_row(ball: &Ball, target_x: usize) -> Option<usize> {
+    intercept(ball.x(), ball.x_velocity(), target_x, ball.y(), ball.y_velocity(), BUFFER_HEIGHT)
+}
+
+/// Returns the column at which **ball** will reach row **target_y**, accounting for bounces
+/// off the left and right edges. Returns None if the ball is not moving toward that row.
+pub fn predict_col(ball: &Ball, target_y: usize) -> Option<usize> {
+    intercept(ball.y(), ball.y_velocity(), target_y, ball.x(), ball.x_velocity(), BUFFER_WIDTH)
+}
+
+// Where a ball at **pos** moving at **velocity** will be along the other axis, starting from
+// **along** and moving at **along_velocity**, when it reaches **target**.
+fn intercept(pos: Fixed, velocity: Fixed, target: usize, along: Fixed, along_velocity: Fixed, size: usize) -> Option<usize> {
+    let distance = Fixed::from_int(target as i32) - pos;
+    if velocity == Fixed::ZERO || distance.signum() != velocity.signum() {
+        return None;
+    }
+    let steps = distance / velocity;
+    Some(fold_into_field(along + along_velocity * steps, size))
+}
+
+/// Maps an unbounded position onto **size** cells, treating the first and last as mirrors.
+fn fold_into_field(pos: Fixed, size: usize) -> usize {
+    let last = Fixed::from_int(size as i32 - 1);
+    let period = last * 2;
+    let folded = pos.rem_euclid(period);
+    (if folded > last { period - folded } else { folded }).round() as usize
+}
+
+pub struct CpuPlayer {
+    profile: AiProfile,
+    approaching: bool,
+    wait: usize,
+    target: Option<usize>,
+    rng: u32,
+}
+
+impl CpuPlayer {
+    pub fn new(profile: AiProfile, seed: u32) -> Self {
+        Self { profile, approaching: false, wait: 0, target: None, rng: seed | 1 }
+    }
+
+    /// Decides how many cells **paddle** should move this tick along its length, negative
+    /// being up or left.
+    pub fn update(&mut self, ball: &Ball, paddle: &Player) -> isize {
+        // The ball approaches across the paddle; the paddle moves along its length.
+        let (velocity, ball_cell, paddle_cell, start, size) = if paddle.is_horizontal() {
+            (ball.y_velocity(), ball.row(), paddle.y, paddle.x, BUFFER_WIDTH)
+        } else {
+            (ball.x_velocity(), ball.col(), paddle.x, paddle.y, BUFFER_HEIGHT)
+        };
+        let approaching = velocity != Fixed::ZERO
+            && (paddle_cell as isize - ball_cell as isize).signum() as i32 == velocity.signum();
+        if approaching && !self.approaching {
+            self.wait = self.profile.reaction_delay;
+            self.target = None;
+        }
+        self.approaching = approaching;
+
+        if self.wait > 0 {
+            self.wait -= 1;
+            return 0;
+        }
+
+        let target = if approaching {
+            if self.target.is_none() {
+                let prediction = if paddle.is_horizontal() { predict_col(ball, paddle.y) } else { predict_row(ball, paddle.x) };
+                self.target = prediction.map(|cell| self.with_error(cell, size));
+            }
+            self.target.unwrap_or(size / 2)
+        } else {
+            size / 2
+        };
+
+        let center = (start + paddle.length() / 2) as isize;
+        let max_speed = self.profile.max_speed as isize;
+        (target as isize - center).clamp(-max_speed, max_speed)
+    }
+
+    fn with_error(&mut self, cell: usize, size: usize) -> usize {
+        let error = self.profile.prediction_error as isize;
+        if error == 0 {
+            return cell;
+        }
+        let offset = (self.next_random() % (2 * error as u32 + 1)) as isize - error;
+        (cell as isize + offset).clamp(0, (size - 1) as isize) as usize
+    }
+
+    // xorshift32
+    fn next_random(&mut self) -> u32 {
+        self.rng ^= self.rng << 13;
+        self.rng ^= self.rng >> 17;
+        self.rng ^= self.rng << 5;
+        self.rng
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn moving(x: i32, y: i32, x_velocity: Fixed, y_velocity: Fixed) -> Ball {
+        let mut ball = Ball::new(0, 0, 1, 1, Fixed::ONE);
+        ball.place(Fixed::from_int(x), Fixed::from_int(y), x_velocity, y_velocity);
+        ball
+    }
+
+    #[test]
+    fn prediction_follows_a_straight_path() {
+        let ball = moving(10, 5, Fixed::ONE, Fixed::HALF);
+        assert_eq!(predict_row(&ball, 20), Some(10));
+        assert_eq!(predict_row(&ball, 5), None);
+        assert_eq!(predict_row(&moving(10, 5, Fixed::ZERO, Fixed::ONE), 20), None);
+    }
+
+    #[test]
+    fn prediction_reflects_off_the_walls() {
+        assert_eq!(predict_row(&moving(10, 20, Fixed::ONE, Fixed::ONE), 20), Some(18));
+        assert_eq!(predict_row(&moving(10, 3, Fixed::ONE, -Fixed::ONE), 20), Some(7));
+        assert_eq!(predict_col(&moving(75, 10, Fixed::ONE, Fixed::ONE), 20), Some(73));
+        assert_eq!(fold_into_field(Fixed::from_int(-50), BUFFER_HEIGHT), 2);
+        assert_eq!(fold_into_field(Fixed::from_int(100), BUFFER_HEIGHT), 4);
+    }
+
+    #[test]
+    fn cpu_waits_out_its_reaction_delay() {
+        let paddle = Player::new(BUFFER_WIDTH - 3, 10);
+        let ball = moving(40, 20, Fixed::ONE, Fixed::ZERO);
+        let mut cpu = CpuPlayer::new(EASY, 7);
+        for _ in 0..EASY.reaction_delay {
+            assert_eq!(cpu.update(&ball, &paddle), 0);
+        }
+        assert_eq!(cpu.update(&ball, &paddle), 1);
+    }
+
+    #[test]
+    fn cpu_moves_no_faster_than_its_profile() {
+        let ball = moving(40, 20, Fixed::ONE, Fixed::ZERO);
+        let mut cpu = CpuPlayer::new(HARD, 7);
+        let far = Player::new(BUFFER_WIDTH - 3, 0);
+        assert_eq!(cpu.update(&ball, &far), 0);
+        assert_eq!(cpu.update(&ball, &far), HARD.max_speed as isize);
+        let near = Player::new(BUFFER_WIDTH - 3, 17);
+        assert_eq!(cpu.update(&ball, &near), 1);
+
+        // With the ball going away it drifts back to the middle.
+        let away = moving(40, 20, -Fixed::ONE, Fixed::ZERO);
+        assert_eq!(cpu.update(&away, &near), -(HARD.max_speed as isize));
+    }
+
+    #[test]
+    fn prediction_error_stays_within_the_profile() {
+        for seed in 0..200 {
+            let mut cpu = CpuPlayer::new(EASY, seed);
+            assert!((7..=17).contains(&cpu.with_error(12, BUFFER_HEIGHT)));
+            assert!(cpu.with_error(0, BUFFER_HEIGHT) <= 5);
+            assert_eq!(CpuPlayer::new(HARD, seed).with_error(12, BUFFER_HEIGHT), 12);
+        }
+    }
+}
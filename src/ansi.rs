@@ -0,0 +1,220 @@
+// ANSI terminal mirror.
+//
+// When QEMU runs with -nographic the VGA text buffer is invisible, but the serial port is
+// wired to the terminal. AnsiScreen draws frames there with ANSI escape sequences, keeping
+// its own copy of what the terminal shows so that only changed cells are sent, each after a
+// cursor move and, when it differs from the last one sent, a color change. AnsiInput turns
+// the bytes the terminal sends back, including the escape sequences for the arrow keys, into
+// the same DecodedKeys the keyboard produces.
+
+use core::fmt::{self, Write};
+use pc_keyboard::{DecodedKey, KeyCode};
+use crate::platform::vga_buffer::{is_drawable, BUFFER_WIDTH, BUFFER_HEIGHT, ColorCode, Color};
+use crate::display::{FrameBuffer, Surface};
+
+const ESC: u8 = 0x1B;
+
+/// The SGR parameter that sets **color** as the foreground. Adding 10 gives the background.
+pub fn ansi_color(color: Color) -> u8 {
+    match color {
+        Color::Black => 30,
+        Color::Red => 31,
+        Color::Green => 32,
+        Color::Brown => 33,
+        Color::Blue => 34,
+        Color::Magenta => 35,
+        Color::Cyan => 36,
+        Color::LightGray => 37,
+        Color::DarkGray => 90,
+        Color::LightRed => 91,
+        Color::LightGreen => 92,
+        Color::Yellow => 93,
+        Color::LightBlue => 94,
+        Color::Pink => 95,
+        Color::LightCyan => 96,
+        Color::White => 97,
+    }
+}
+
+/// What an ANSI terminal is showing, and how to bring it up to date with a frame.
+pub struct AnsiScreen {
+    front: FrameBuffer,
+    front_valid: bool,
+}
+
+impl AnsiScreen {
+    pub fn new() -> Self {
+        Self { front: FrameBuffer::new(), front_valid: false }
+    }
+
+    /// Forgets what the terminal shows, so the next **render()** clears it and redraws
+    /// every cell.
+    pub fn invalidate(&mut self) {
+        self.front_valid = false;
+    }
+
+    /// Writes the escape sequences and characters that turn the terminal's screen into
+    /// **frame** to **out**. Returns the number of cells written.
+    pub fn render<W: Write>(&mut self, frame: &FrameBuffer, out: &mut W) -> Result<usize, fmt::Error> {
+        let mut cursor = None;
+        let mut color: Option<ColorCode> = None;
+        if !self.front_valid {
+            // Reset the colors, clear the screen and hide the cursor.
+            out.write_str("\x1b[0m\x1b[2J\x1b[?25l")?;
+        }
+        let mut written = 0;
+        for row in 0..BUFFER_HEIGHT {
+            for col in 0..BUFFER_WIDTH {
+                let cell = frame.cell(col, row);
+                if self.front_valid && cell == self.front.cell(col, row) {
+                    continue;
+                }
+                if cursor != Some((col, row)) {
+                    write!(out, "\x1b[{};{}H", row + 1, col + 1)?;
+                }
+                if color != Some(cell.color) {
+                    let (fg, bg) = (cell.color.foreground(), cell.color.background());
+                    write!(out, "\x1b[{};{}m", ansi_color(fg), ansi_color(bg) + 10)?;
+                    color = Some(cell.color);
+                }
+                out.write_char(if is_drawable(cell.ch) { cell.ch } else { '?' })?;
+                // Terminals differ on where the cursor goes after the last column.
+                cursor = (col + 1 < BUFFER_WIDTH).then_some((col + 1, row));
+                self.front.plot(cell.ch, col, row, cell.color);
+                written += 1;
+            }
+        }
+        self.front_valid = true;
+        Ok(written)
+    }
+}
+
+impl Default for AnsiScreen {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Decoding {
+    Ground,
+    // After ESC, and whether a tick has passed since.
+    Escape { waited: bool },
+    // After ESC [ or ESC O, skipping parameters until the final byte.
+    Sequence,
+}
+
+/// Decodes the bytes a terminal sends, one at a time, into key presses.
+///
+/// A terminal sends the Esc key as a lone ESC byte, which is also how the arrow keys'
+/// sequences begin, so an ESC only counts as Esc once a tick passes with nothing after it.
+pub struct AnsiInput {
+    state: Decoding,
+}
+
+impl AnsiInput {
+    pub fn new() -> Self {
+        Self { state: Decoding::Ground }
+    }
+
+    /// Takes the next **byte** from the terminal, returning the key it completes, if any.
+    pub fn push(&mut self, byte: u8) -> Option<DecodedKey> {
+        match (self.state, byte) {
+            (Decoding::Ground, ESC) => {
+                self.state = Decoding::Escape { waited: false };
+                None
+            }
+            (Decoding::Ground, b'\r' | b'\n') => Some(DecodedKey::RawKey(KeyCode::Enter)),
+            (Decoding::Ground, byte) => byte.is_ascii().then_some(DecodedKey::Unicode(byte as char)),
+            (Decoding::Escape { .. }, b'[' | b'O') => {
+                self.state = Decoding::Sequence;
+                None
+            }
+            (Decoding::Escape { .. }, ESC) => {
+                // The first ESC was the Esc key, and this one starts over.
+                self.state = Decoding::Escape { waited: false };
+                Some(DecodedKey::RawKey(KeyCode::Escape))
+            }
+            (Decoding::Escape { .. }, byte) => {
+                // Alt held with a key; the key alone will do.
+                self.state = Decoding::Ground;
+                self.push(byte)
+            }
+            (Decoding::Sequence, 0x40..=0x7E) => {
+                self.state = Decoding::Ground;
+                match byte {
+                    b'A' => Some(DecodedKey::RawKey(KeyCode::ArrowUp)),
+                    b'B' => Some(DecodedKey::RawKey(KeyCode::ArrowDown)),
+                    b'C' => Some(DecodedKey::RawKey(KeyCode::ArrowRight)),
+                    b'D' => Some(DecodedKey::RawKey(KeyCode::ArrowLeft)),
+                    _ => None,
+                }
+            }
+            (Decoding::Sequence, _) => None,
+        }
+    }
+
+    /// Call once per tick. Returns Esc if an ESC byte has gone a whole tick with nothing
+    /// after it.
+    pub fn tick(&mut self) -> Option<DecodedKey> {
+        match self.state {
+            Decoding::Escape { waited: false } => {
+                self.state = Decoding::Escape { waited: true };
+                None
+            }
+            Decoding::Escape { waited: true } => {
+                self.state = Decoding::Ground;
+                Some(DecodedKey::RawKey(KeyCode::Escape))
+            }
+            _ => None,
+        }
+    }
+}
+
+impl Default for AnsiInput {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ansi_mirror_sends_only_changed_cells() {
+        let mut frame = FrameBuffer::new();
+        frame.plot_str("FOOTY-PONG", 35, 5, ColorCode::new(Color::White, Color::Black));
+        let mut terminal = AnsiScreen::new();
+        let mut out = String::new();
+        assert_eq!(terminal.render(&frame, &mut out), Ok(BUFFER_WIDTH * BUFFER_HEIGHT));
+        assert!(out.starts_with("\x1b[0m\x1b[2J"));
+        assert!(out.contains("FOOTY-PONG"));
+
+        let color = ColorCode::new(Color::Yellow, Color::Blue);
+        frame.plot_str("ab", 10, 3, color);
+        out.clear();
+        assert_eq!(terminal.render(&frame, &mut out), Ok(2));
+        assert_eq!(out, "\x1b[4;11H\x1b[93;44mab");
+        out.clear();
+        assert_eq!(terminal.render(&frame, &mut out), Ok(0));
+        assert_eq!(out, "");
+    }
+
+    #[test]
+    fn terminal_bytes_decode_to_keys() {
+        let mut input = AnsiInput::new();
+        let keys: Vec<DecodedKey> = b"w\x1b[A\x1bOD\r\x1b[5~".iter().filter_map(|&byte| input.push(byte)).collect();
+        assert_eq!(keys, vec![
+            DecodedKey::Unicode('w'),
+            DecodedKey::RawKey(KeyCode::ArrowUp),
+            DecodedKey::RawKey(KeyCode::ArrowLeft),
+            DecodedKey::RawKey(KeyCode::Enter),
+        ]);
+        // A lone ESC is the Esc key once a whole tick passes without more of a sequence.
+        assert_eq!(input.push(0x1B), None);
+        assert_eq!(input.tick(), None);
+        assert_eq!(input.tick(), Some(DecodedKey::RawKey(KeyCode::Escape)));
+        assert_eq!(input.tick(), None);
+    }
+}
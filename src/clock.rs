@@ -0,0 +1,74 @@
+// Match clock.
+//
+// Counts timer ticks while the ball is in play. A timed match is split into equal halves or
+// periods; an untimed one just counts up. Game stops the clock between points and whenever
+// it is not in the Playing state by simply not calling tick().
+
+use core::fmt::Write;
+use crate::TICKS_PER_SECOND;
+use crate::display::TextLine;
+
+pub struct MatchClock {
+    periods: u32,
+    period: u32,
+    // Length of each period, or None for an untimed match.
+    period_ticks: Option<usize>,
+    elapsed: usize,
+}
+
+impl MatchClock {
+    /// A clock for a match lasting **time_limit** seconds in total, split into **periods**.
+    pub fn new(time_limit: Option<u32>, periods: u32) -> Self {
+        let periods = periods.max(1);
+        let period_ticks = time_limit.map(|seconds| seconds as usize * TICKS_PER_SECOND / periods as usize);
+        Self { periods, period: 1, period_ticks, elapsed: 0 }
+    }
+
+    /// Advances the clock by one timer tick. It stops at the end of each period.
+    pub fn tick(&mut self) {
+        if !self.period_over() {
+            self.elapsed += 1;
+        }
+    }
+
+    pub fn is_timed(&self) -> bool {
+        self.period_ticks.is_some()
+    }
+
+    /// The current period, starting from 1.
+    pub fn period(&self) -> u32 {
+        self.period
+    }
+
+    pub fn periods(&self) -> u32 {
+        self.periods
+    }
+
+    /// Whether the current period has run out.
+    pub fn period_over(&self) -> bool {
+        self.period_ticks.is_some_and(|length| self.elapsed >= length)
+    }
+
+    /// Whether the last period has run out.
+    pub fn time_up(&self) -> bool {
+        self.period == self.periods && self.period_over()
+    }
+
+    /// Starts the next period once the current one is over.
+    pub fn next_period(&mut self) {
+        if self.period_over() && self.period < self.periods {
+            self.period += 1;
+            self.elapsed = 0;
+        }
+    }
+
+    /// Writes MM:SS: the time left in the period for a timed match, or the time played.
+    pub fn write_time(&self, line: &mut TextLine) {
+        let ticks = match self.period_ticks {
+            Some(length) => length.saturating_sub(self.elapsed),
+            None => self.elapsed,
+        };
+        let seconds = ticks.div_ceil(TICKS_PER_SECOND);
+        let _ = write!(line, "{:02}:{:02}", seconds / 60, seconds % 60);
+    }
+}
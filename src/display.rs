@@ -0,0 +1,290 @@
+// Drawing surfaces for the game.
+//
+// Game code draws through the Surface trait rather than calling the VGA plotting functions
+// directly. FrameBuffer keeps an 80x25 grid of cells in memory, so frames can be inspected
+// on the host; VgaSurface forwards every call to the VGA text buffer. DoubleBuffer composes
+// each frame in memory and copies only the cells that changed to the VGA buffer, and
+// optionally to a terminal on the serial port.
+
+use core::fmt;
+use crate::ansi::AnsiScreen;
+use crate::platform::serial::SERIAL1;
+use crate::platform::vga_buffer::{self, BUFFER_WIDTH, BUFFER_HEIGHT, ColorCode, Color};
+use x86_64::instructions::port::PortReadOnly;
+
+/// VGA input status register #1. Bit 3 is set while the display is in vertical retrace.
+const VGA_INPUT_STATUS_PORT: u16 = 0x3DA;
+const VERTICAL_RETRACE: u8 = 0x08;
+
+/// Something that can display an 80x25 grid of colored characters.
+pub trait Surface {
+    /// Plots the given character at the given location with the given color.
+    /// It will **panic** on an illegal row or column.
+    fn plot(&mut self, c: char, col: usize, row: usize, color: ColorCode);
+
+    /// Returns the character and color at the specified coordinates.
+    /// It will **panic** given an illegal row or column.
+    fn peek(&self, col: usize, row: usize) -> (char, ColorCode);
+
+    /// Displays the specified string at the given coordinates.
+    /// If the string exceeds the width of the buffer, it will be truncated.
+    /// Returns the next column to use after the call.
+    fn plot_str(&mut self, s: &str, col: usize, row: usize, color: ColorCode) -> usize {
+        let end = BUFFER_WIDTH.min(col + s.len());
+        for (c, chr) in (col..end).zip(s.chars()) {
+            self.plot(chr, c, row, color);
+        }
+        end % BUFFER_WIDTH
+    }
+
+    /// Displays the specified string centered on the given row.
+    /// Returns the next column to use after the call.
+    fn plot_centered(&mut self, s: &str, row: usize, color: ColorCode) -> usize {
+        let col = (BUFFER_WIDTH / 2).saturating_sub(s.len() / 2);
+        self.plot_str(s, col, row, color)
+    }
+
+    /// Displays the given number at the specified coordinates.
+    /// Returns the next column to use after the call.
+    fn plot_num(&mut self, num: isize, col: usize, row: usize, color: ColorCode) -> usize {
+        if num == 0 {
+            self.plot('0', col, row, color);
+            (col + 1) % BUFFER_WIDTH
+        } else if num < 0 {
+            self.plot('-', col, row, color);
+            self.plot_num(-num, col + 1, row, color)
+        } else {
+            let mut buffer = [' '; BUFFER_WIDTH];
+            let mut c = 0;
+            let mut num = num;
+            while num > 0 && c + col < buffer.len() {
+                buffer[c] = ((num % 10 + '0' as isize) as u8) as char;
+                num /= 10;
+                c += 1;
+            }
+            for (i, digit) in buffer[..c].iter().enumerate() {
+                self.plot(*digit, col + c - i - 1, row, color);
+            }
+            (col + c) % BUFFER_WIDTH
+        }
+    }
+
+    /// Sets every cell to a blank in the given color.
+    fn fill(&mut self, color: ColorCode) {
+        for row in 0..BUFFER_HEIGHT {
+            for col in 0..BUFFER_WIDTH {
+                self.plot(' ', col, row, color);
+            }
+        }
+    }
+}
+
+/// A single character cell of a frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cell {
+    pub ch: char,
+    pub color: ColorCode,
+}
+
+impl Cell {
+    pub fn blank() -> Self {
+        Self { ch: ' ', color: ColorCode::new(Color::Black, Color::Black) }
+    }
+}
+
+/// An in-memory 80x25 frame.
+#[derive(Clone)]
+pub struct FrameBuffer {
+    cells: [[Cell; BUFFER_WIDTH]; BUFFER_HEIGHT],
+}
+
+impl FrameBuffer {
+    pub fn new() -> Self {
+        Self { cells: [[Cell::blank(); BUFFER_WIDTH]; BUFFER_HEIGHT] }
+    }
+
+    /// Returns the cell at the specified coordinates.
+    /// It will **panic** given an illegal row or column.
+    pub fn cell(&self, col: usize, row: usize) -> Cell {
+        self.cells[row][col]
+    }
+
+    /// Returns the characters of the given row, left to right.
+    pub fn row_chars(&self, row: usize) -> impl Iterator<Item = char> + '_ {
+        self.cells[row].iter().map(|cell| cell.ch)
+    }
+}
+
+impl Default for FrameBuffer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Surface for FrameBuffer {
+    fn plot(&mut self, c: char, col: usize, row: usize, color: ColorCode) {
+        self.cells[row][col] = Cell { ch: c, color };
+    }
+
+    fn peek(&self, col: usize, row: usize) -> (char, ColorCode) {
+        let cell = self.cells[row][col];
+        (cell.ch, cell.color)
+    }
+}
+
+/// A line of text no wider than the screen, assembled with `write!` since there is no heap.
+/// Text beyond BUFFER_WIDTH characters is dropped.
+pub struct TextLine {
+    bytes: [u8; BUFFER_WIDTH],
+    len: usize,
+}
+
+impl TextLine {
+    pub fn new() -> Self {
+        Self { bytes: [0; BUFFER_WIDTH], len: 0 }
+    }
+
+    pub fn as_str(&self) -> &str {
+        // Only whole characters are ever copied in, so this cannot fail.
+        core::str::from_utf8(&self.bytes[..self.len]).unwrap_or("")
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn clear(&mut self) {
+        self.len = 0;
+    }
+}
+
+impl Default for TextLine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Write for TextLine {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        for c in s.chars() {
+            let width = c.len_utf8();
+            if self.len + width > BUFFER_WIDTH {
+                break;
+            }
+            c.encode_utf8(&mut self.bytes[self.len..]);
+            self.len += width;
+        }
+        Ok(())
+    }
+}
+
+/// Draws straight into the VGA text buffer.
+pub struct VgaSurface;
+
+impl Surface for VgaSurface {
+    fn plot(&mut self, c: char, col: usize, row: usize, color: ColorCode) {
+        vga_buffer::plot(c, col, row, color);
+    }
+
+    fn peek(&self, col: usize, row: usize) -> (char, ColorCode) {
+        vga_buffer::peek(col, row)
+    }
+}
+
+/// A back buffer that the game composes frames into, plus a copy of what is currently
+/// on the screen. **flush()** writes only the cells that differ between the two.
+pub struct DoubleBuffer {
+    back: FrameBuffer,
+    front: FrameBuffer,
+    front_valid: bool,
+    vsync: bool,
+    serial: Option<AnsiScreen>,
+}
+
+impl DoubleBuffer {
+    pub fn new() -> Self {
+        Self { back: FrameBuffer::new(), front: FrameBuffer::new(), front_valid: false, vsync: true, serial: None }
+    }
+
+    /// Chooses whether **flush()** also draws each frame on a terminal attached to SERIAL1,
+    /// using ANSI escape sequences.
+    pub fn set_serial_mirror(&mut self, mirror: bool) {
+        self.serial = mirror.then(AnsiScreen::new);
+    }
+
+    /// Chooses whether **flush()** waits for the start of vertical retrace before writing.
+    pub fn set_vsync(&mut self, vsync: bool) {
+        self.vsync = vsync;
+    }
+
+    /// Forgets what is on the screen, so the next **flush()** rewrites every cell.
+    /// Call this after anything else has written to the VGA buffer.
+    pub fn invalidate(&mut self) {
+        self.front_valid = false;
+        if let Some(serial) = &mut self.serial {
+            serial.invalidate();
+        }
+    }
+
+    /// The frame being composed.
+    pub fn back(&self) -> &FrameBuffer {
+        &self.back
+    }
+
+    /// Copies every changed cell of the back buffer to the VGA buffer, and to the serial
+    /// terminal if mirroring. Returns the number of cells written to the VGA buffer.
+    pub fn flush(&mut self) -> usize {
+        if self.vsync {
+            wait_for_vertical_retrace();
+        }
+        let mut written = 0;
+        for row in 0..BUFFER_HEIGHT {
+            for col in 0..BUFFER_WIDTH {
+                let cell = self.back.cells[row][col];
+                if !self.front_valid || cell != self.front.cells[row][col] {
+                    vga_buffer::plot(cell.ch, col, row, cell.color);
+                    self.front.cells[row][col] = cell;
+                    written += 1;
+                }
+            }
+        }
+        self.front_valid = true;
+        if let Some(serial) = &mut self.serial {
+            // A frame the terminal misses is redrawn in full next time.
+            if serial.render(&self.back, &mut *SERIAL1.lock()).is_err() {
+                serial.invalidate();
+            }
+        }
+        written
+    }
+}
+
+impl Default for DoubleBuffer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Surface for DoubleBuffer {
+    fn plot(&mut self, c: char, col: usize, row: usize, color: ColorCode) {
+        self.back.plot(c, col, row, color);
+    }
+
+    fn peek(&self, col: usize, row: usize) -> (char, ColorCode) {
+        self.back.peek(col, row)
+    }
+}
+
+/// Busy-waits until the display enters vertical retrace. If a retrace is already underway,
+/// it waits for the next one so that the whole retrace period is available for writing.
+pub fn wait_for_vertical_retrace() {
+    let mut status: PortReadOnly<u8> = PortReadOnly::new(VGA_INPUT_STATUS_PORT);
+    unsafe {
+        while status.read() & VERTICAL_RETRACE != 0 {}
+        while status.read() & VERTICAL_RETRACE == 0 {}
+    }
+}
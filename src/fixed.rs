@@ -0,0 +1,159 @@
+// 16.16 fixed-point numbers.
+//
+// The kernel is built with SSE disabled and soft-float (see x86_64-blog_os.json), so ball
+// positions and velocities use integer fixed-point arithmetic to move by fractions of a cell.
+
+use core::fmt;
+use core::ops::{Add, AddAssign, Div, Mul, Neg, Sub, SubAssign};
+
+const FRAC_BITS: u32 = 16;
+
+/// A signed number with 16 integer bits and 16 fractional bits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub struct Fixed(i32);
+
+impl Fixed {
+    pub const ZERO: Fixed = Fixed(0);
+    pub const ONE: Fixed = Fixed(1 << FRAC_BITS);
+    pub const HALF: Fixed = Fixed(1 << (FRAC_BITS - 1));
+
+    pub const fn from_int(n: i32) -> Self {
+        Fixed(n << FRAC_BITS)
+    }
+
+    /// Returns **num** / **den**, rounded toward zero.
+    pub const fn from_ratio(num: i32, den: i32) -> Self {
+        Fixed((((num as i64) << FRAC_BITS) / den as i64) as i32)
+    }
+
+    /// The nearest integer, with halves rounded up.
+    pub const fn round(self) -> i32 {
+        (self.0 + Self::HALF.0) >> FRAC_BITS
+    }
+
+    pub const fn abs(self) -> Self {
+        Fixed(self.0.abs())
+    }
+
+    pub const fn signum(self) -> i32 {
+        self.0.signum()
+    }
+
+    /// The remainder of dividing by **modulus**, always in `0..modulus`.
+    pub fn rem_euclid(self, modulus: Self) -> Self {
+        Fixed(self.0.rem_euclid(modulus.0))
+    }
+}
+
+/// Shows the number to two decimal places, e.g. "-0.75".
+impl fmt::Display for Fixed {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let sign = if self.0 < 0 { "-" } else { "" };
+        let hundredths = (self.0.unsigned_abs() as u64 * 100 + (1 << (FRAC_BITS - 1))) >> FRAC_BITS;
+        write!(f, "{}{}.{:02}", sign, hundredths / 100, hundredths % 100)
+    }
+}
+
+impl Add for Fixed {
+    type Output = Fixed;
+    fn add(self, rhs: Fixed) -> Fixed {
+        Fixed(self.0 + rhs.0)
+    }
+}
+
+impl AddAssign for Fixed {
+    fn add_assign(&mut self, rhs: Fixed) {
+        self.0 += rhs.0;
+    }
+}
+
+impl Sub for Fixed {
+    type Output = Fixed;
+    fn sub(self, rhs: Fixed) -> Fixed {
+        Fixed(self.0 - rhs.0)
+    }
+}
+
+impl SubAssign for Fixed {
+    fn sub_assign(&mut self, rhs: Fixed) {
+        self.0 -= rhs.0;
+    }
+}
+
+impl Neg for Fixed {
+    type Output = Fixed;
+    fn neg(self) -> Fixed {
+        Fixed(-self.0)
+    }
+}
+
+impl Mul for Fixed {
+    type Output = Fixed;
+    fn mul(self, rhs: Fixed) -> Fixed {
+        Fixed(((self.0 as i64 * rhs.0 as i64) >> FRAC_BITS) as i32)
+    }
+}
+
+impl Mul<i32> for Fixed {
+    type Output = Fixed;
+    fn mul(self, rhs: i32) -> Fixed {
+        Fixed(self.0 * rhs)
+    }
+}
+
+/// Saturates at the largest or smallest Fixed when the quotient is out of range, as it is
+/// for a tiny divisor.
+impl Div for Fixed {
+    type Output = Fixed;
+    fn div(self, rhs: Fixed) -> Fixed {
+        let quotient = ((self.0 as i64) << FRAC_BITS) / rhs.0 as i64;
+        Fixed(quotient.clamp(i32::MIN as i64, i32::MAX as i64) as i32)
+    }
+}
+
+impl Div<i32> for Fixed {
+    type Output = Fixed;
+    fn div(self, rhs: i32) -> Fixed {
+        Fixed(self.0 / rhs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn multiplies_and_divides() {
+        let three_halves = Fixed::from_ratio(3, 2);
+        assert_eq!(three_halves * Fixed::from_int(4), Fixed::from_int(6));
+        assert_eq!(three_halves * -Fixed::HALF, -Fixed::from_ratio(3, 4));
+        assert_eq!(three_halves * 2, Fixed::from_int(3));
+        assert_eq!(Fixed::from_int(3) / three_halves, Fixed::from_int(2));
+        assert_eq!(Fixed::from_int(-3) / Fixed::from_int(4), Fixed::from_ratio(-3, 4));
+        assert_eq!(Fixed::from_int(7) / 2, Fixed::from_ratio(7, 2));
+    }
+
+    #[test]
+    fn division_by_a_tiny_number_saturates() {
+        let tiny = Fixed::ONE / (1 << 16);
+        assert_eq!(Fixed::from_int(1000) / tiny, Fixed(i32::MAX));
+        assert_eq!(Fixed::from_int(-1000) / tiny, Fixed(i32::MIN));
+    }
+
+    #[test]
+    fn rounds_halves_up() {
+        assert_eq!(Fixed::from_ratio(5, 2).round(), 3);
+        assert_eq!(Fixed::from_ratio(12, 5).round(), 2);
+        assert_eq!(Fixed::from_ratio(-5, 2).round(), -2);
+        assert_eq!(Fixed::from_ratio(-13, 5).round(), -3);
+    }
+
+    #[test]
+    fn displays_two_decimal_places() {
+        assert_eq!(format!("{}", Fixed::from_ratio(-3, 4)), "-0.75");
+        assert_eq!(format!("{}", Fixed::from_int(40)), "40.00");
+        assert_eq!(format!("{}", Fixed::from_ratio(1, 3)), "0.33");
+        assert_eq!(format!("{}", Fixed::from_ratio(2, 3)), "0.67");
+        assert_eq!(format!("{}", Fixed::ZERO), "0.00");
+    }
+}
@@ -0,0 +1,169 @@
+// Headless simulation harness.
+//
+// Runs a Game against an in-memory FrameBuffer instead of the VGA buffer, so scripted
+// matches can be played and checked on the host without booting QEMU. Each module's tests
+// use it for their scripted runs, and the QEMU tests under tests/ script the game with the
+// same Step timeline.
+
+use crate::display::FrameBuffer;
+use crate::platform::vga_buffer::{BUFFER_HEIGHT, BUFFER_WIDTH};
+use crate::Game;
+use pc_keyboard::{DecodedKey, KeyCode, KeyEvent, KeyState};
+
+/// One entry of a scripted timeline.
+#[derive(Debug, Clone)]
+pub enum Step {
+    /// Delivers a decoded key press to the game.
+    Key(DecodedKey),
+    /// Delivers a key going up or down to the game.
+    Event(KeyEvent),
+    /// Advances the game by the given number of timer ticks.
+    Ticks(usize),
+}
+
+impl Step {
+    pub fn char(c: char) -> Self {
+        Step::Key(DecodedKey::Unicode(c))
+    }
+
+    pub fn raw(code: KeyCode) -> Self {
+        Step::Key(DecodedKey::RawKey(code))
+    }
+
+    pub fn press(code: KeyCode) -> Self {
+        Step::Event(KeyEvent::new(code, KeyState::Down))
+    }
+
+    pub fn release(code: KeyCode) -> Self {
+        Step::Event(KeyEvent::new(code, KeyState::Up))
+    }
+}
+
+pub struct Simulation {
+    game: Game,
+    screen: FrameBuffer,
+    ticks: usize,
+}
+
+impl Simulation {
+    pub fn new() -> Self {
+        Self::from_game(Game::new())
+    }
+
+    /// A simulation that has started a match from the main menu, choosing **mode** and
+    /// **difficulty** by their hotkeys.
+    pub fn start_match(mode: char, difficulty: char) -> Self {
+        let mut sim = Self::new();
+        sim.run(&[
+            Step::Ticks(1),
+            Step::raw(KeyCode::Enter),
+            Step::Ticks(1),
+            Step::char(mode),
+            Step::Ticks(1),
+            Step::char(difficulty),
+        ]);
+        sim
+    }
+
+    pub fn from_game(game: Game) -> Self {
+        Self {
+            game,
+            screen: FrameBuffer::new(),
+            ticks: 0,
+        }
+    }
+
+    pub fn key(&mut self, key: DecodedKey) -> &mut Self {
+        self.game.key(key);
+        self
+    }
+
+    pub fn key_event(&mut self, event: &KeyEvent) -> &mut Self {
+        self.game.key_event(event);
+        self
+    }
+
+    pub fn ticks(&mut self, n: usize) -> &mut Self {
+        for _ in 0..n {
+            self.game.tick(&mut self.screen);
+            self.ticks += 1;
+        }
+        self
+    }
+
+    /// Plays every step of **timeline** in order.
+    pub fn run(&mut self, timeline: &[Step]) -> &mut Self {
+        for step in timeline {
+            match step {
+                Step::Key(key) => {
+                    self.key(*key);
+                }
+                Step::Event(event) => {
+                    self.key_event(event);
+                }
+                Step::Ticks(n) => {
+                    self.ticks(*n);
+                }
+            }
+        }
+        self
+    }
+
+    /// Ticks until **done** returns true or **max_ticks** have elapsed.
+    /// Returns whether **done** was satisfied.
+    pub fn run_until<F: Fn(&Game) -> bool>(&mut self, max_ticks: usize, done: F) -> bool {
+        for _ in 0..max_ticks {
+            if done(&self.game) {
+                return true;
+            }
+            self.ticks(1);
+        }
+        done(&self.game)
+    }
+
+    pub fn game(&self) -> &Game {
+        &self.game
+    }
+
+    pub fn game_mut(&mut self) -> &mut Game {
+        &mut self.game
+    }
+
+    pub fn screen(&self) -> &FrameBuffer {
+        &self.screen
+    }
+
+    pub fn elapsed_ticks(&self) -> usize {
+        self.ticks
+    }
+
+    /// Returns the row and column where **text** appears in the last rendered frame.
+    pub fn find_text(&self, text: &str) -> Option<(usize, usize)> {
+        let len = text.chars().count();
+        if len == 0 || len > BUFFER_WIDTH {
+            return None;
+        }
+        for row in 0..BUFFER_HEIGHT {
+            let mut chars = [' '; BUFFER_WIDTH];
+            for (slot, c) in chars.iter_mut().zip(self.screen.row_chars(row)) {
+                *slot = c;
+            }
+            for col in 0..=(BUFFER_WIDTH - len) {
+                if chars[col..col + len].iter().copied().eq(text.chars()) {
+                    return Some((row, col));
+                }
+            }
+        }
+        None
+    }
+
+    pub fn screen_contains(&self, text: &str) -> bool {
+        self.find_text(text).is_some()
+    }
+}
+
+impl Default for Simulation {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,304 @@
+// Keyboard input plumbing.
+//
+// KeyQueue carries key events from the keyboard interrupt handler to the main loop without
+// locks, so a burst of key presses between two polls is never lost. InputState turns those
+// events into the set of game actions whose keys are currently held down.
+
+use pc_keyboard::{DecodedKey, KeyCode, KeyEvent, KeyState};
+use core::cell::UnsafeCell;
+use core::mem::MaybeUninit;
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+/// A fixed-capacity, lock-free single-producer/single-consumer ring buffer.
+///
+/// Exactly one context (e.g. the keyboard interrupt handler) may call **push()**, and exactly
+/// one other context (e.g. the main loop) may call **pop()**. Events that arrive while the
+/// queue is full are dropped and counted by **overflows()**, which the kernel prints in its
+/// crash report.
+pub struct KeyQueue<T, const N: usize> {
+    slots: [UnsafeCell<MaybeUninit<T>>; N],
+    // Both indices increase forever (wrapping); a slot is index % N.
+    head: AtomicUsize,
+    tail: AtomicUsize,
+    overflows: AtomicUsize,
+}
+
+// The producer only writes slots the consumer has released, and the consumer only reads
+// slots the producer has published, so the queue can be shared between the two.
+unsafe impl<T: Send, const N: usize> Sync for KeyQueue<T, N> {}
+
+impl<T, const N: usize> KeyQueue<T, N> {
+    pub const fn new() -> Self {
+        Self {
+            slots: [const { UnsafeCell::new(MaybeUninit::uninit()) }; N],
+            head: AtomicUsize::new(0),
+            tail: AtomicUsize::new(0),
+            overflows: AtomicUsize::new(0),
+        }
+    }
+
+    /// Adds **item** to the back of the queue. Producer side only.
+    /// Returns false, and counts an overflow, if the queue was full.
+    pub fn push(&self, item: T) -> bool {
+        let tail = self.tail.load(Ordering::Relaxed);
+        let head = self.head.load(Ordering::Acquire);
+        if tail.wrapping_sub(head) >= N {
+            self.overflows.fetch_add(1, Ordering::Relaxed);
+            return false;
+        }
+        unsafe {
+            (*self.slots[tail % N].get()).write(item);
+        }
+        self.tail.store(tail.wrapping_add(1), Ordering::Release);
+        true
+    }
+
+    /// Removes the item at the front of the queue. Consumer side only.
+    pub fn pop(&self) -> Option<T> {
+        let head = self.head.load(Ordering::Relaxed);
+        let tail = self.tail.load(Ordering::Acquire);
+        if head == tail {
+            return None;
+        }
+        let item = unsafe { (*self.slots[head % N].get()).assume_init_read() };
+        self.head.store(head.wrapping_add(1), Ordering::Release);
+        Some(item)
+    }
+
+    /// The number of items dropped so far because the queue was full.
+    pub fn overflows(&self) -> usize {
+        self.overflows.load(Ordering::Relaxed)
+    }
+}
+
+impl<T, const N: usize> Default for KeyQueue<T, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, const N: usize> Drop for KeyQueue<T, N> {
+    fn drop(&mut self) {
+        while self.pop().is_some() {}
+    }
+}
+
+/// A game control that can be bound to a key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    P1Up,
+    P1Down,
+    P2Up,
+    P2Down,
+    /// Steers player 1's paddle when it lies along the bottom of the screen.
+    P1Left,
+    P1Right,
+    /// Steer players 3 and 4, whose paddles lie along the top and bottom of the screen.
+    P3Left,
+    P3Right,
+    P4Left,
+    P4Right,
+}
+
+const NUM_ACTIONS: usize = 10;
+
+impl Action {
+    fn index(self) -> usize {
+        self as usize
+    }
+}
+
+/// The actions that move the paddle in **seat** back (up or left) and forward (down or
+/// right). Player 1 steers with left and right when their paddle is **horizontal**.
+pub fn paddle_actions(seat: usize, horizontal: bool) -> (Action, Action) {
+    match (seat, horizontal) {
+        (0, false) => (Action::P1Up, Action::P1Down),
+        (0, true) => (Action::P1Left, Action::P1Right),
+        (1, _) => (Action::P2Up, Action::P2Down),
+        (2, _) => (Action::P3Left, Action::P3Right),
+        _ => (Action::P4Left, Action::P4Right),
+    }
+}
+
+/// Maps a physical key to an action. **ch** is the character the key decodes to, which is
+/// used when only decoded key presses are available.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Binding {
+    pub action: Action,
+    pub code: KeyCode,
+    pub ch: Option<char>,
+}
+
+impl Binding {
+    pub const fn new(action: Action, code: KeyCode, ch: Option<char>) -> Self {
+        Self { action, code, ch }
+    }
+
+    fn matches_decoded(&self, key: DecodedKey) -> bool {
+        match key {
+            DecodedKey::RawKey(code) => code == self.code,
+            DecodedKey::Unicode(c) => self.ch.is_some_and(|ch| ch.eq_ignore_ascii_case(&c)),
+        }
+    }
+}
+
+pub const DEFAULT_BINDINGS: [Binding; 12] = [
+    Binding::new(Action::P1Up, KeyCode::W, Some('w')),
+    Binding::new(Action::P1Down, KeyCode::S, Some('s')),
+    Binding::new(Action::P2Up, KeyCode::ArrowUp, None),
+    Binding::new(Action::P2Down, KeyCode::ArrowDown, None),
+    Binding::new(Action::P1Left, KeyCode::A, Some('a')),
+    Binding::new(Action::P1Right, KeyCode::D, Some('d')),
+    Binding::new(Action::P1Left, KeyCode::ArrowLeft, None),
+    Binding::new(Action::P1Right, KeyCode::ArrowRight, None),
+    Binding::new(Action::P3Left, KeyCode::J, Some('j')),
+    Binding::new(Action::P3Right, KeyCode::L, Some('l')),
+    Binding::new(Action::P4Left, KeyCode::Numpad4, Some('4')),
+    Binding::new(Action::P4Right, KeyCode::Numpad6, Some('6')),
+];
+
+/// Tracks which bound actions are held down.
+///
+/// Key up/down events give exact held state. When a source only reports decoded key presses,
+/// each press is remembered as a tap instead, to be consumed once by the next game tick.
+/// The keyboard reports both for every key, so after its first up/down event its decoded
+/// presses are ignored. A terminal only ever sends presses, which always count as taps.
+pub struct InputState {
+    bindings: &'static [Binding],
+    held: [bool; NUM_ACTIONS],
+    taps: [usize; NUM_ACTIONS],
+    key_events_seen: bool,
+}
+
+impl InputState {
+    pub fn new() -> Self {
+        Self::with_bindings(&DEFAULT_BINDINGS)
+    }
+
+    pub fn with_bindings(bindings: &'static [Binding]) -> Self {
+        Self { bindings, held: [false; NUM_ACTIONS], taps: [0; NUM_ACTIONS], key_events_seen: false }
+    }
+
+    /// Records a key going up or down.
+    pub fn handle_event(&mut self, event: &KeyEvent) {
+        self.key_events_seen = true;
+        let down = event.state == KeyState::Down;
+        for binding in self.bindings.iter().filter(|b| b.code == event.code) {
+            self.held[binding.action.index()] = down;
+        }
+    }
+
+    /// Records a decoded key press from the keyboard, unless its up/down events are
+    /// already being tracked.
+    pub fn handle_press(&mut self, key: DecodedKey) {
+        if !self.key_events_seen {
+            self.handle_tap(key);
+        }
+    }
+
+    /// Records a key press from a source that cannot report releases, such as a terminal.
+    pub fn handle_tap(&mut self, key: DecodedKey) {
+        for binding in self.bindings.iter().filter(|b| b.matches_decoded(key)) {
+            self.taps[binding.action.index()] += 1;
+        }
+    }
+
+    pub fn is_held(&self, action: Action) -> bool {
+        self.held[action.index()]
+    }
+
+    /// Returns and clears the number of decoded presses of **action** since the last call.
+    pub fn take_taps(&mut self, action: Action) -> usize {
+        core::mem::take(&mut self.taps[action.index()])
+    }
+
+    /// Forgets all held keys and pending taps.
+    pub fn release_all(&mut self) {
+        self.held = [false; NUM_ACTIONS];
+        self.taps = [0; NUM_ACTIONS];
+    }
+}
+
+impl Default for InputState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::rc::Rc;
+
+    #[test]
+    fn queue_pops_in_push_order() {
+        let queue: KeyQueue<u32, 4> = KeyQueue::new();
+        assert_eq!(queue.pop(), None);
+        for i in 1..=3 {
+            assert!(queue.push(i));
+        }
+        assert_eq!(queue.pop(), Some(1));
+        assert!(queue.push(4));
+        assert_eq!([queue.pop(), queue.pop(), queue.pop(), queue.pop()], [Some(2), Some(3), Some(4), None]);
+    }
+
+    #[test]
+    fn queue_wraps_around_its_slots() {
+        // Batches of 3 in 4 slots start at a different slot each time round.
+        let queue: KeyQueue<usize, 4> = KeyQueue::new();
+        for batch in 0..10 {
+            for i in 0..3 {
+                assert!(queue.push(batch * 3 + i));
+            }
+            for i in 0..3 {
+                assert_eq!(queue.pop(), Some(batch * 3 + i));
+            }
+        }
+        assert_eq!(queue.pop(), None);
+        assert_eq!(queue.overflows(), 0);
+    }
+
+    #[test]
+    fn full_queue_drops_and_counts_overflows() {
+        let queue: KeyQueue<char, 2> = KeyQueue::new();
+        assert!(queue.push('a'));
+        assert!(queue.push('b'));
+        assert!(!queue.push('c'));
+        assert!(!queue.push('d'));
+        assert_eq!(queue.overflows(), 2);
+        assert_eq!(queue.pop(), Some('a'));
+        assert!(queue.push('e'));
+        assert_eq!([queue.pop(), queue.pop(), queue.pop()], [Some('b'), Some('e'), None]);
+        assert_eq!(queue.overflows(), 2);
+    }
+
+    #[test]
+    fn dropping_the_queue_drops_what_is_left() {
+        let item = Rc::new(());
+        {
+            let queue: KeyQueue<Rc<()>, 4> = KeyQueue::new();
+            for _ in 0..3 {
+                queue.push(Rc::clone(&item));
+            }
+            drop(queue.pop());
+            assert_eq!(Rc::strong_count(&item), 3);
+        }
+        assert_eq!(Rc::strong_count(&item), 1);
+    }
+
+    #[test]
+    fn terminal_taps_count_after_keyboard_events() {
+        let mut input = InputState::new();
+        input.handle_press(DecodedKey::Unicode('w'));
+        assert_eq!(input.take_taps(Action::P1Up), 1);
+
+        input.handle_event(&KeyEvent::new(KeyCode::W, KeyState::Down));
+        assert!(input.is_held(Action::P1Up));
+        input.handle_press(DecodedKey::Unicode('w'));
+        assert_eq!(input.take_taps(Action::P1Up), 0);
+
+        input.handle_tap(DecodedKey::Unicode('w'));
+        input.handle_tap(DecodedKey::Unicode('w'));
+        assert_eq!(input.take_taps(Action::P1Up), 2);
+    }
+}
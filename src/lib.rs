@@ -1,25 +1,118 @@
 #![cfg_attr(not(test), no_std)]
-
-use pluggable_interrupt_os::vga_buffer::{BUFFER_WIDTH, BUFFER_HEIGHT, plot, plot_num, plot_str, ColorCode, Color};
-use pc_keyboard::{DecodedKey, KeyCode};
+#![feature(abi_x86_interrupt)]
+
+pub mod ai;
+pub mod ansi;
+pub mod clock;
+pub mod display;
+pub mod fixed;
+#[cfg(any(test, feature = "harness"))]
+pub mod harness;
+pub mod input;
+pub mod menu;
+pub mod modes;
+pub mod physics;
+pub mod platform;
+pub mod rules;
+pub mod theme;
+
+use crate::platform::vga_buffer::{BUFFER_WIDTH, BUFFER_HEIGHT, ColorCode, Color};
+use pc_keyboard::{DecodedKey, KeyCode, KeyEvent};
+use display::Surface;
+use input::{Action, InputState};
+use ai::CpuPlayer;
+use fixed::Fixed;
+use physics::{Block, Contact, Side};
+use rules::{MatchRules, Outcome};
+use modes::{GameMode, ModeState, MAX_BLOCKS, MODES};
+use modes::footy::Footy;
+use clock::MatchClock;
+use menu::{Menu, MenuAction, MenuItem};
+use display::TextLine;
+use theme::{Palette, Theme, THEMES};
+use core::fmt::{self, Write};
+
+/// Approximate rate of the PIT timer interrupt that drives `tick`. The real rate is about
+/// 18.2 Hz, so the match clock runs a little slow.
+pub const TICKS_PER_SECOND: usize = 18;
 
 const PADDLE_HEIGHT: usize = 5;
 
+/// The most players a mode can seat. Player 1 sits in seat 0.
+pub const MAX_PLAYERS: usize = 4;
+
+// Outgoing slope (rows per column) for a ball striking each row of a paddle, top to bottom.
+const PADDLE_ZONES: [Fixed; PADDLE_HEIGHT] = [
+    Fixed::from_int(-1), Fixed::from_ratio(-1, 2), Fixed::ZERO, Fixed::from_ratio(1, 2), Fixed::from_int(1),
+];
+const MAX_Y_VELOCITY: Fixed = Fixed::from_ratio(3, 2);
+// Horizontal speed gained each time a paddle returns the ball.
+const SPEED_RAMP: Fixed = Fixed::from_ratio(1, 32);
+// Spin changes the y-velocity by up to MAX_SPIN * SPIN_ACCELERATION every tick
+// for SPIN_DURATION ticks after the ball leaves a paddle.
+const MAX_SPIN: isize = 2;
+const SPIN_ACCELERATION: Fixed = Fixed::from_ratio(1, 16);
+const SPIN_DURATION: usize = 8;
+// How long the ball waits before being served after a point.
+const SERVE_DELAY_TICKS: usize = TICKS_PER_SECOND / 2;
+
+const MAIN_MENU: [MenuItem; 3] = [
+    MenuItem::new("Start Game", None),
+    MenuItem::new("[H]ow to Play", Some('h')),
+    MenuItem::new("[S]ettings", Some('s')),
+];
+const HOW_TO_PLAY_MENU: [MenuItem; 1] = [MenuItem::new("[H] Back", Some('h'))];
+const DIFFICULTY_MENU: [MenuItem; 4] = [
+    MenuItem::new("[0] Multiplayer", Some('0')),
+    MenuItem::new("[1] Easy", Some('1')),
+    MenuItem::new("[2] Medium", Some('2')),
+    MenuItem::new("[3] Hard", Some('3')),
+];
+const DIFFICULTIES: [Difficulty; 4] = [Difficulty::Multiplayer, Difficulty::Easy, Difficulty::Medium, Difficulty::Hard];
+const SETTINGS_MENU: [MenuItem; 5] = [
+    MenuItem::new("Target score", None),
+    MenuItem::new("Win by", None),
+    MenuItem::new("Time limit", None),
+    MenuItem::new("Golden goal", None),
+    MenuItem::new("Theme", None),
+];
+const HALFTIME_MENU: [MenuItem; 1] = [MenuItem::new("Continue", None)];
+const PAUSE_MENU: [MenuItem; 4] = [
+    MenuItem::new("Resume", Some(' ')),
+    MenuItem::new("[R]estart match", Some('r')),
+    MenuItem::new("Change [S]ettings", Some('s')),
+    MenuItem::new("[Q]uit to main menu", Some('q')),
+];
+const SEAT_MENU: [MenuItem; 5] = [
+    MenuItem::new("Player 1 (left)", None),
+    MenuItem::new("Player 2 (right)", None),
+    MenuItem::new("Player 3 (top)", None),
+    MenuItem::new("Player 4 (bottom)", None),
+    MenuItem::new("[S]tart", Some('s')),
+];
+const GAME_OVER_MENU: [MenuItem; 2] = [
+    MenuItem::new("[M]ain Menu", Some('m')),
+    MenuItem::new("[R]estart", Some('r')),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum GameState {
     MainMenu,
     HowToPlay,
+    Settings,
     SelectGameMode,
     DifficultySelect,
+    /// Choosing which seats the CPU plays, for modes with more than two players.
+    SeatSelect,
     Playing,
+    /// Between halves or periods of a timed match.
+    Halftime,
+    /// Play is frozen under the pause menu.
+    Paused,
     GameOver,
 }
 
-pub enum GameMode {
-    Footy,
-    Hockey,
-    Tennis,
-}
-
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Difficulty {
     Multiplayer,
     Easy, 
@@ -27,600 +120,765 @@ pub enum Difficulty {
     Hard,
 }
 
+/// What the game was doing at one moment, for crash reports.
+#[derive(Debug, Clone, Copy)]
+pub struct Snapshot {
+    pub state: GameState,
+    pub mode: &'static str,
+    pub difficulty: Difficulty,
+    pub tick: isize,
+    pub scores: (u32, u32),
+    /// Position then velocity.
+    pub ball: (Fixed, Fixed, Fixed, Fixed),
+    /// The top-left cell of each paddle in play, by seat.
+    pub paddles: [Option<(usize, usize)>; MAX_PLAYERS],
+}
+
+impl fmt::Display for Snapshot {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Game: {:?} in {} ({:?}), tick {}", self.state, self.mode, self.difficulty, self.tick)?;
+        writeln!(f, "Score: {} - {}", self.scores.0, self.scores.1)?;
+        let (x, y, x_velocity, y_velocity) = self.ball;
+        writeln!(f, "Ball: at ({}, {}) moving ({}, {})", x, y, x_velocity, y_velocity)?;
+        write!(f, "Paddles:")?;
+        for (seat, paddle) in self.paddles.iter().enumerate() {
+            if let Some((x, y)) = paddle {
+                write!(f, "  P{} ({}, {})", seat + 1, x, y)?;
+            }
+        }
+        writeln!(f)
+    }
+}
+
 pub struct Game {
-    player1: Player,
-    player2: Player,
+    players: [Player; MAX_PLAYERS],
     tick_count: isize,
     ball: Ball,
     score1: u32,
     score2: u32,
-    ball_speed: isize,
-    game_mode: GameMode,
+    game_mode: &'static dyn GameMode,
+    // What the current mode remembers during a match. Always the mode's own variant.
+    mode_state: ModeState,
     game_state: GameState,
     difficulty: Difficulty,
+    input: InputState,
+    paddle_interval: usize,
+    cpus: [Option<CpuPlayer>; MAX_PLAYERS],
+    // The seats the CPU is to play in the next match.
+    cpu_seats: [bool; MAX_PLAYERS],
+    rules: MatchRules,
+    outcome: Option<Outcome>,
+    clock: MatchClock,
+    // Whether the players have switched ends, putting player 1 on the right.
+    sides_switched: bool,
+    // The menu shown on the current screen.
+    menu: Menu,
+    // Index into THEMES.
+    theme: usize,
+    // The screen to go back to when leaving the settings screen.
+    settings_return: GameState,
+    // Set when the pause menu is dismissed, so the next tick redraws the frozen frame
+    // without the overlay before play continues.
+    redraw_frozen: bool,
+    // Ticks until the ball is back in play after a point or a face-off. The clock stops meanwhile.
+    restart_ticks: usize,
 }
 
 impl Game {
     pub fn new() -> Self {
         Self {
-            // player1: Player::new(2, BUFFER_HEIGHT / 2 - PADDLE_HEIGHT / 2),
-            player1: Player::new(2, BUFFER_HEIGHT / 2 - PADDLE_HEIGHT / 2, 1),
-            // player2: Player::new(BUFFER_WIDTH - 3, BUFFER_HEIGHT / 2 - PADDLE_HEIGHT / 2),
-            player2: Player::new(BUFFER_WIDTH - 3, BUFFER_HEIGHT / 2 - PADDLE_HEIGHT / 2, 3),
+            players: Footy.paddles(),
             tick_count: 0,
-            ball: Ball::new(BUFFER_WIDTH / 2, BUFFER_HEIGHT / 2, 1, 1),
+            ball: Ball::new(BUFFER_WIDTH / 2, BUFFER_HEIGHT / 2, 1, 1, Footy.serve_speed()),
             score1: 0,
             score2: 0,
-            ball_speed: 2,
-            game_mode: GameMode::Footy,
+            game_mode: &Footy,
+            mode_state: Footy.new_state(),
             game_state: GameState::MainMenu,
             difficulty: Difficulty::Multiplayer,
+            input: InputState::new(),
+            paddle_interval: 1,
+            cpus: [const { None }; MAX_PLAYERS],
+            cpu_seats: [false; MAX_PLAYERS],
+            rules: MatchRules::default(),
+            outcome: None,
+            clock: MatchClock::new(None, 1),
+            sides_switched: false,
+            menu: Menu::new("FOOTY-PONG", &MAIN_MENU),
+            theme: 0,
+            settings_return: GameState::MainMenu,
+            redraw_frozen: false,
+            restart_ticks: 0,
+        }
+    }
+
+    pub fn state(&self) -> GameState {
+        self.game_state
+    }
+
+    pub fn mode(&self) -> &'static dyn GameMode {
+        self.game_mode
+    }
+
+    pub fn difficulty(&self) -> Difficulty {
+        self.difficulty
+    }
+
+    pub fn scores(&self) -> (u32, u32) {
+        (self.score1, self.score2)
+    }
+
+    pub fn ball(&self) -> &Ball {
+        &self.ball
+    }
+
+    pub fn player1(&self) -> &Player {
+        &self.players[0]
+    }
+
+    pub fn player2(&self) -> &Player {
+        &self.players[1]
+    }
+
+    /// The paddle in **seat**, 0 being player 1's.
+    pub fn player(&self, seat: usize) -> &Player {
+        &self.players[seat]
+    }
+
+    /// Whether the CPU plays the paddle in **seat** this match.
+    pub fn is_cpu(&self, seat: usize) -> bool {
+        self.cpus[seat].is_some()
+    }
+
+    pub fn rules(&self) -> MatchRules {
+        self.rules
+    }
+
+    pub fn set_rules(&mut self, rules: MatchRules) {
+        self.rules = rules;
+    }
+
+    pub fn theme(&self) -> &'static Theme {
+        &THEMES[self.theme]
+    }
+
+    /// The colors play is drawn in: the theme's palette, or the current sport's own.
+    pub fn palette(&self) -> Palette {
+        self.theme().palette.unwrap_or_else(|| self.game_mode.palette())
+    }
+
+    pub fn clock(&self) -> &MatchClock {
+        &self.clock
+    }
+
+    /// Where everything stands right now, for a crash report.
+    pub fn snapshot(&self) -> Snapshot {
+        let mode = self.game_mode;
+        Snapshot {
+            state: self.game_state,
+            mode: mode.name(),
+            difficulty: self.difficulty,
+            tick: self.tick_count,
+            scores: (self.score1, self.score2),
+            ball: (self.ball.x, self.ball.y, self.ball.x_velocity, self.ball.y_velocity),
+            paddles: core::array::from_fn(|seat| {
+                mode.seat_active(self, seat).then(|| (self.players[seat].x, self.players[seat].y))
+            }),
         }
     }
 
+    /// How the last match ended, once it is over.
+    pub fn outcome(&self) -> Option<Outcome> {
+        self.outcome
+    }
+
+    /// Sets how many ticks pass between paddle moves while a key is held.
+    pub fn set_paddle_interval(&mut self, ticks: usize) {
+        self.paddle_interval = ticks.max(1);
+    }
+
+    /// Tracks key up/down events for paddle control.
+    pub fn key_event(&mut self, event: &KeyEvent) {
+        self.input.handle_event(event);
+    }
+
+    /// Handles a key press decoded by the keyboard.
     pub fn key(&mut self, key: DecodedKey) {
-        match key {
-            DecodedKey::RawKey(key) => {
-                match key {
-                    KeyCode::ArrowUp => {
-                        self.player2.move_up();
-                    }
-                    KeyCode::ArrowDown => {
-                        self.player2.move_down();
-                    }
-                    KeyCode::Enter => {
-                        if let GameState::MainMenu = self.game_state {
-                            self.game_state = GameState::SelectGameMode;
-                        }
-                    }
-                    _ => {}
-                }
-            },
-            DecodedKey::Unicode(key) => {
-                match key {
-                    'm' => {
-                        if let GameState::GameOver = self.game_state {
-                            self.restart_game();
-                            self.game_state = GameState::MainMenu;
-                        }
-                    }
-                    // 'p' => {
-                    //     self.score1 = 6;
-                    // }
-                    'w' => {
-                        self.player1.move_up();
-                    }
-                    's' => {
-                        self.player1.move_down();
-                    }
-                    'r' => {
-                        self.restart_game();
-                    }
-                    '\n' | '\r' => { // Handle 'Enter' key press in Unicode case as well
-                        if let GameState::MainMenu = self.game_state {
-                            self.game_state = GameState::SelectGameMode;
-                        }
-                    }
-                    // ' ' => {
-                    //     // PAUSE GAME ?
-                    // }
-                    'f' => {
-                        if let GameState::SelectGameMode = self.game_state {
-                            self.game_mode = GameMode::Footy;
-                            self.game_state = GameState::DifficultySelect;
-                        }
-                    }
-                    'h' => {
-                        if let GameState::SelectGameMode = self.game_state {
-                            self.game_mode = GameMode::Hockey;
-                            self.game_state = GameState::DifficultySelect;
-                        } else if let GameState::MainMenu = self.game_state {
-                            self.game_state = GameState::HowToPlay;
-                        } else if let GameState::HowToPlay = self.game_state {
-                            self.game_state = GameState::MainMenu;
-                        }
-                    }
-                    't' => {
-                        if let GameState::SelectGameMode = self.game_state {
-                            self.game_mode = GameMode::Tennis;
-                            self.game_state = GameState::DifficultySelect;
-                        }
-                    }
-                    '0' => {
-                        if let GameState::DifficultySelect = self.game_state {
-                            self.difficulty = Difficulty::Multiplayer;
-                            self.game_state = GameState::Playing;
-                        }
-                    }
-                    '1' => {
-                        if let GameState::DifficultySelect = self.game_state {
-                            self.difficulty = Difficulty::Easy;
-                            self.game_state = GameState::Playing;
-                        }
-                    }
-                    '2' => {
-                        if let GameState::DifficultySelect = self.game_state {
-                            self.difficulty = Difficulty::Medium;
-                            self.game_state = GameState::Playing;
-                        }
-                    }
-                    '3' => {
-                        if let GameState::DifficultySelect = self.game_state {
-                            self.difficulty = Difficulty::Hard;
-                            self.game_state = GameState::Playing;
-                        }
-                    }
-                    _ => {}
-                }
+        self.press(key, false);
+    }
+
+    /// Handles a key typed on a terminal. A terminal cannot report releases, so its paddle
+    /// keys always count as taps, even once the keyboard's up/down events are being tracked.
+    pub fn terminal_key(&mut self, key: DecodedKey) {
+        self.press(key, true);
+    }
+
+    fn press(&mut self, key: DecodedKey, from_terminal: bool) {
+        if let GameState::Playing = self.game_state {
+            if from_terminal {
+                self.input.handle_tap(key);
+            } else {
+                self.input.handle_press(key);
             }
+            if let DecodedKey::Unicode(' ' | '\u{1b}') | DecodedKey::RawKey(KeyCode::Escape) = key {
+                self.pause();
+            }
+        } else if let Some(action) = self.menu.handle_key(key) {
+            self.menu_action(action);
         }
     }
 
-    pub fn tick(&mut self) {
-        match self.game_state {
-            GameState::MainMenu => {
-                self.clear_screen();
-                self.display_main_menu();
+    fn menu_action(&mut self, action: MenuAction) {
+        match (self.game_state, action) {
+            (GameState::MainMenu, MenuAction::Confirm(0)) => self.set_state(GameState::SelectGameMode),
+            (GameState::MainMenu, MenuAction::Confirm(1)) => self.set_state(GameState::HowToPlay),
+            (GameState::MainMenu, MenuAction::Confirm(_)) => self.open_settings(),
+            (GameState::HowToPlay, MenuAction::Confirm(_) | MenuAction::Back) => self.set_state(GameState::MainMenu),
+            (GameState::SelectGameMode, MenuAction::Confirm(i)) => {
+                self.set_mode(MODES[i]);
+                if self.game_mode.players() == 1 {
+                    self.select_difficulty(Difficulty::Multiplayer);
+                } else {
+                    self.set_state(GameState::DifficultySelect);
+                }
             }
-            GameState::HowToPlay => {
-                self.clear_screen();
-                self.draw_how_to_play();
+            (GameState::SelectGameMode, MenuAction::Back) => self.set_state(GameState::MainMenu),
+            (GameState::DifficultySelect, MenuAction::Confirm(i)) => self.select_difficulty(DIFFICULTIES[i]),
+            (GameState::DifficultySelect, MenuAction::Back) => self.set_state(GameState::SelectGameMode),
+            (GameState::SeatSelect, MenuAction::Confirm(seat) | MenuAction::Adjust(seat, _)) if seat < MAX_PLAYERS => {
+                self.cpu_seats[seat] = !self.cpu_seats[seat];
             }
+            (GameState::SeatSelect, MenuAction::Confirm(_)) => self.start_play(),
+            (GameState::SeatSelect, MenuAction::Back) => self.set_state(GameState::DifficultySelect),
+            (GameState::Settings, MenuAction::Adjust(row, delta)) => self.adjust_setting(row, delta),
+            (GameState::Settings, MenuAction::Confirm(_) | MenuAction::Back) => self.set_state(self.settings_return),
+            (GameState::Halftime, MenuAction::Confirm(_)) => self.start_next_period(),
+            (GameState::Paused, MenuAction::Confirm(0) | MenuAction::Back) => self.resume(),
+            (GameState::Paused, MenuAction::Confirm(1)) => self.restart_game(),
+            (GameState::Paused, MenuAction::Confirm(2)) => self.open_settings(),
+            (GameState::Paused, MenuAction::Confirm(_)) => self.go_main_menu(),
+            (GameState::GameOver, MenuAction::Confirm(0) | MenuAction::Back) => self.go_main_menu(),
+            (GameState::GameOver, MenuAction::Confirm(_)) => self.restart_game(),
+            _ => {}
+        }
+    }
+
+    /// Switches to **mode**, along with a fresh state for it.
+    fn set_mode(&mut self, mode: &'static dyn GameMode) {
+        self.game_mode = mode;
+        self.mode_state = mode.new_state();
+    }
+
+    /// Moves to **state**, showing its menu.
+    fn set_state(&mut self, state: GameState) {
+        self.game_state = state;
+        self.menu = self.menu_for(state);
+    }
+
+    fn menu_for(&self, state: GameState) -> Menu {
+        match state {
+            GameState::MainMenu => Menu::new("FOOTY-PONG", &MAIN_MENU),
+            GameState::HowToPlay => Menu::new("How to Play:", &HOW_TO_PLAY_MENU),
+            GameState::Settings => Menu::new("Match Settings:", &SETTINGS_MENU),
             GameState::SelectGameMode => {
-                self.clear_screen();
-                self.display_game_mode_menu();
+                let mut menu = Menu::new("Select Game Mode:", &MODES.map(|mode| mode.menu_item()));
+                menu.select(MODES.iter().position(|mode| mode.name() == self.game_mode.name()).unwrap_or(0));
+                menu
             }
             GameState::DifficultySelect => {
-                self.clear_screen();
-                self.display_difficulty_menu();
+                let mut menu = Menu::new("Select Difficulty:", &DIFFICULTY_MENU);
+                menu.select(DIFFICULTIES.iter().position(|d| *d == self.difficulty).unwrap_or(0));
+                menu
+            }
+            GameState::SeatSelect => {
+                let mut menu = Menu::new("Choose Players:", &SEAT_MENU);
+                menu.select(SEAT_MENU.len() - 1);
+                menu
+            }
+            GameState::Halftime => {
+                let title = match (self.clock.periods(), self.clock.period()) {
+                    (2, _) => "HALF TIME",
+                    (_, 1) => "END OF PERIOD 1",
+                    _ => "END OF PERIOD 2",
+                };
+                Menu::new(title, &HALFTIME_MENU)
+            }
+            GameState::Paused => Menu::new("PAUSED", &PAUSE_MENU),
+            GameState::GameOver => {
+                let title = self.game_mode.outcome_title(self.outcome.unwrap_or(Outcome::Draw));
+                Menu::new(title, &GAME_OVER_MENU)
+            }
+            GameState::Playing => Menu::new("", &[]),
+        }
+    }
+
+    pub fn tick<S: Surface>(&mut self, screen: &mut S) {
+        match self.game_state {
+            GameState::MainMenu
+            | GameState::HowToPlay
+            | GameState::Settings
+            | GameState::SelectGameMode
+            | GameState::DifficultySelect
+            | GameState::SeatSelect
+            | GameState::Halftime
+            | GameState::GameOver => {
+                self.clear_screen(screen);
+                self.display_menu(screen);
+            }
+            GameState::Playing if self.redraw_frozen => {
+                self.redraw_frozen = false;
+                self.draw_frozen_frame(screen);
             }
             GameState::Playing => {
-                match self.difficulty {
-                    Difficulty::Multiplayer => {
-                        self.cpu_move();
-                    }
-                    Difficulty::Easy => {
-                        self.cpu_move();
-                    }
-                    Difficulty::Medium => {
-                        self.cpu_move();
-                    }
-                    Difficulty::Hard => {
-                        self.cpu_move();
-                    }
-                }
+                self.move_paddles();
+                self.cpu_move();
                 self.tick_count += 1;
-                let background_color = match self.game_mode {
-                    GameMode::Footy => Color::Green,
-                    GameMode::Hockey => Color::White,
-                    GameMode::Tennis => Color::Blue,
+                let palette = self.palette();
+                self.draw_field(screen);
+                self.render(screen);
+                let contact = if self.restart_ticks > 0 {
+                    self.restart_ticks -= 1;
+                    self.game_mode.draw_restart(self, screen);
+                    None
+                } else {
+                    self.clock.tick();
+                    self.step_ball()
                 };
-                self.clear_screen_playing(background_color);
-                match self.game_mode {
-                    GameMode::Footy => {
-                        self.draw_soccer_field();
-                    }
-                    GameMode::Tennis => {
-                        self.draw_tennis_court();
-                    }
-                    GameMode::Hockey => {
-                        self.draw_hockey_rink();
-                    }
+                self.ball.render(screen, palette.ball, palette.field);
+                self.handle_collisions(contact);
+                let mode = self.game_mode;
+                mode.after_step(self);
+                for player in &mut self.players {
+                    player.settle();
                 }
-                self.render();
-                let ball_color = match self.game_mode {
-                    GameMode::Footy => Color::White,
-                    GameMode::Hockey => Color::Black,
-                    GameMode::Tennis => Color::Green,
-                };
-                self.ball.update_position(ball_color, background_color);
-                self.handle_collisions();
+                self.display_clock(screen, palette);
         
                 // Check for game over
-                if self.score1 == 7 || self.score2 == 7 {
-                    let winner = if self.score1 == 7 { 1 } else { 2 };
-                    self.display_winner_message(winner);
-                    self.game_state = GameState::GameOver;
+                if let Some(outcome) = self.check_for_winner() {
+                    self.outcome = Some(outcome);
+                    self.set_state(GameState::GameOver);
+                    self.display_menu(screen);
+                } else if self.clock.period_over() && !self.clock.time_up() {
+                    self.set_state(GameState::Halftime);
                 }
             }
-            GameState::GameOver => {
-                self.clear_screen();
-                let winner = if self.score1 == 7 { 1 } else { 2 };
-                self.display_winner_message(winner);
+            GameState::Paused => {
+                self.draw_frozen_frame(screen);
+                self.display_pause_menu(screen);
             }
         }
     }
     
-    fn cpu_move(&mut self) {
-        let ball_y = self.ball.y as isize;
-        let player2_y = self.player2.y as isize;
-        let player2_max_velocity = self.player2.max_velocity as isize;
-
-        let velocity = match self.difficulty {
-            Difficulty::Multiplayer => 0,
-            Difficulty::Easy => 1,
-            Difficulty::Medium => 2,
-            Difficulty::Hard => 3,
-            _ => 0,
-        };
-
-        if velocity > 0 {
-            let distance = ball_y - player2_y;
-            let direction = distance.signum();
-            let move_amount = (velocity * direction).min(player2_max_velocity);
-            self.player2.y = (self.player2.y as isize + move_amount).max(0).min((BUFFER_HEIGHT - PADDLE_HEIGHT) as isize) as usize;
+    fn move_paddles(&mut self) {
+        let held_step = (self.tick_count as usize).is_multiple_of(self.paddle_interval);
+        let mode = self.game_mode;
+        for seat in 0..MAX_PLAYERS {
+            let (back, forward) = input::paddle_actions(seat, self.players[seat].is_horizontal());
+            let motion = self.paddle_motion(back, forward, held_step);
+            if mode.seat_active(self, seat) {
+                self.players[seat].shift(motion);
+            }
         }
     }
-    
-    fn display_main_menu(&self) {
-        let game_name = "FOOTY-PONG";
-        let game_name_x = (BUFFER_WIDTH / 2).saturating_sub(game_name.len() / 2);
-        let game_name_y = BUFFER_HEIGHT / 2 - 2;
-        let game_name_color = ColorCode::new(Color::Yellow, Color::Black);
-        plot_str(game_name, game_name_x, game_name_y, game_name_color);
-
-        let main_menu_message = "Press ENTER to start";
-        let message_x = (BUFFER_WIDTH / 2).saturating_sub(main_menu_message.len() / 2);
-        let message_y = BUFFER_HEIGHT / 2 + 1;
-        let color = ColorCode::new(Color::White, Color::Black);
-        plot_str(main_menu_message, message_x, message_y, color);
-
-        let htp = "[H]ow to Play";
-        let htp_x = (BUFFER_WIDTH / 2).saturating_sub(htp.len() / 2);
-        let htp_y = message_y + 1;
-        plot_str(htp, htp_x, htp_y, color);
-
-    }
-
-    fn display_game_mode_menu(&self) {
-        let game_mode_message = "Select Game Mode:";
-        let game_mode_x = (BUFFER_WIDTH / 2).saturating_sub(game_mode_message.len() / 2);
-        let game_mode_y = BUFFER_HEIGHT / 2 - 2;
-        let color = ColorCode::new(Color::Yellow, Color::Black);
-        plot_str(game_mode_message, game_mode_x, game_mode_y, color);
 
-        let modes = [
-            ("[F]ooty ", GameMode::Footy),
-            ("[H]ockey", GameMode::Hockey),
-            ("[T]ennis", GameMode::Tennis),
-        ];
-
-        for (i, (label, _)) in modes.iter().enumerate() {
-            let x = (BUFFER_WIDTH / 2).saturating_sub(label.len() / 2);
-            let y = game_mode_y + i as usize + 2;
-            plot_str(label, x, y, ColorCode::new(Color::White, Color::Black));
+    fn paddle_motion(&mut self, up: Action, down: Action, held_step: bool) -> isize {
+        let mut motion = self.input.take_taps(down) as isize - self.input.take_taps(up) as isize;
+        if held_step {
+            if self.input.is_held(up) {
+                motion -= 1;
+            }
+            if self.input.is_held(down) {
+                motion += 1;
+            }
         }
+        motion
     }
-    
-    fn display_difficulty_menu(&self) {
-        let gd_message = "Select Difficulty:";
-        let gd_x = (BUFFER_WIDTH / 2).saturating_sub(gd_message.len() / 2);
-        let gd_y = BUFFER_HEIGHT / 2 - 2;
-        let color = ColorCode::new(Color::Yellow, Color::Black);
-        plot_str(gd_message, gd_x, gd_y, color);
 
-        let modes = [
-            ("[0] Multiplayer", Difficulty::Multiplayer),
-            ("[1] Easy       ", Difficulty::Easy),
-            ("[2] Medium     ", Difficulty::Medium),
-            ("[3] Hard       ", Difficulty::Hard),
-        ];
-
-        for (i, (label, _)) in modes.iter().enumerate() {
-            let x = (BUFFER_WIDTH / 2).saturating_sub(label.len() / 2);
-            let y = gd_y + i as usize + 2;
-            plot_str(label, x, y, ColorCode::new(Color::White, Color::Black));
+    /// Against the CPU, it plays player 2 and, in modes for more players, every seat after
+    /// that until changed on the seat menu.
+    fn select_difficulty(&mut self, difficulty: Difficulty) {
+        self.difficulty = difficulty;
+        let cpu = difficulty != Difficulty::Multiplayer;
+        self.cpu_seats = [false, cpu, cpu, cpu];
+        if self.game_mode.players() > 2 {
+            self.set_state(GameState::SeatSelect);
+        } else {
+            self.start_play();
         }
     }
 
-    pub fn draw_soccer_field(&self) {
-        let field_color = ColorCode::new(Color::White, Color::Green);
-
-        // Draw the outer boundary
-        for y in 0..BUFFER_HEIGHT {
-            plot('|', 0, y, field_color);
-            plot('|', BUFFER_WIDTH - 1, y, field_color);
-        }
-        for x in 0..BUFFER_WIDTH {
-            plot('-', x, 0, field_color);
-            plot('-', x, BUFFER_HEIGHT - 1, field_color);
-        }
+    /// Puts the CPU in the seats chosen for it and starts the match. The CPU plays at the
+    /// chosen difficulty, or medium if Multiplayer was chosen.
+    fn start_play(&mut self) {
+        let profile = ai::profile_for(self.difficulty).unwrap_or(ai::MEDIUM);
+        let seed = self.tick_count as u32;
+        let seats = self.cpu_seats;
+        self.cpus = core::array::from_fn(|seat| {
+            seats[seat].then(|| CpuPlayer::new(profile, seed.wrapping_add(seat as u32)))
+        });
+        self.start_match();
+        self.set_state(GameState::Playing);
+    }
 
-        // Draw the halfway line
-        let halfway_x = BUFFER_WIDTH / 2;
-        for y in 0..BUFFER_HEIGHT {
-            plot('|', halfway_x, y, field_color);
-        }
+    /// Resets the clock and ends for a new match and puts the ball in play.
+    fn start_match(&mut self) {
+        self.clock = MatchClock::new(self.match_length(), self.game_mode.periods());
+        let mode = self.game_mode;
+        self.players = mode.paddles();
+        self.sides_switched = false;
+        self.mode_state = mode.new_state();
+        self.serve(1);
+    }
 
-        // Draw the penalty boxes
-        let box_width = BUFFER_WIDTH / 6; // Decrease the width
-        let box_height = (3 * BUFFER_HEIGHT) / 4; // Increase the height
-        let top_y = (BUFFER_HEIGHT - box_height) / 2;
-        let bottom_y = top_y + box_height;
+    /// Leaves the halftime screen: the players switch ends and play restarts from the center.
+    fn start_next_period(&mut self) {
+        self.clock.next_period();
+        let [player1, player2, ..] = &mut self.players;
+        core::mem::swap(&mut player1.x, &mut player2.x);
+        player1.y = BUFFER_HEIGHT / 2 - PADDLE_HEIGHT / 2;
+        player2.y = BUFFER_HEIGHT / 2 - PADDLE_HEIGHT / 2;
+        self.sides_switched = !self.sides_switched;
+        self.input.release_all();
+        self.serve(1);
+        self.set_state(GameState::Playing);
+    }
 
-        // Left penalty box
-        for y in top_y..=bottom_y {
-            plot('|', box_width, y, field_color);
-        }
-        for x in 0..=box_width {
-            plot('-', x, top_y, field_color);
-            plot('-', x, bottom_y, field_color);
-        }
+    /// The player defending the **side** end of the field.
+    fn defender(&self, side: Side) -> usize {
+        if (side == Side::Left) != self.sides_switched { 0 } else { 1 }
+    }
 
-        // Right penalty box
-        for y in top_y..=bottom_y {
-            plot('|', BUFFER_WIDTH - box_width - 1, y, field_color);
-        }
-        for x in (BUFFER_WIDTH - box_width)..BUFFER_WIDTH {
-            plot('-', x, top_y, field_color);
-            plot('-', x, bottom_y, field_color);
-        }
+    /// Puts the ball back in play after a short pause, as the current mode serves it.
+    /// **x_dir** is the direction the ball heads if it restarts from the center.
+    fn serve(&mut self, x_dir: isize) {
+        self.restart_ticks = SERVE_DELAY_TICKS;
+        let mode = self.game_mode;
+        mode.serve(self, x_dir);
+    }
 
-        // Center circle
-        // TBD
+    fn pause(&mut self) {
+        self.set_state(GameState::Paused);
+    }
 
-        // Penalty arcs
-        // TBD
+    fn resume(&mut self) {
+        self.redraw_frozen = true;
+        self.set_state(GameState::Playing);
+    }
 
+    fn open_settings(&mut self) {
+        self.settings_return = self.game_state;
+        self.set_state(GameState::Settings);
     }
-   
-    pub fn draw_tennis_court(&self) {
-        let court_color = ColorCode::new(Color::White, Color::Blue);
-    
-        // Draw the outer boundary
-        for y in 0..BUFFER_HEIGHT {
-            plot('|', 0, y, court_color);
-            plot('|', BUFFER_WIDTH - 1, y, court_color);
-        }
-        for x in 0..BUFFER_WIDTH {
-            plot('-', x, 0, court_color);
-            plot('-', x, BUFFER_HEIGHT - 1, court_color);
 
-            plot('-', x, 2, court_color);
-            plot('-', x, BUFFER_HEIGHT - 3, court_color);
-        }
-    
-        // Draw the horizontal line
-        let horizontal_y = BUFFER_HEIGHT / 2;
-        for x in BUFFER_WIDTH / 4..BUFFER_WIDTH * 3 / 4 {
-            plot('-', x, horizontal_y, court_color);
-        }
-    
-        // Draw the vertical lines
-        let vertical_x1 = BUFFER_WIDTH / 4;
-        // let vertical_x2 = BUFFER_WIDTH / 2;
-        let vertical_x3 = BUFFER_WIDTH * 3 / 4;
-    
-        for y in 3..BUFFER_HEIGHT -3 {
-            plot('|', vertical_x1, y, court_color);
-            plot('|', vertical_x3, y, court_color);
-            // plot('|', vertical_x2, y, court_color);
+    fn adjust_setting(&mut self, row: usize, delta: i32) {
+        match row {
+            0 => self.rules.adjust_target_score(delta),
+            1 => self.rules.adjust_win_by(delta),
+            2 => self.rules.adjust_time_limit(delta),
+            3 => self.rules.golden_goal = !self.rules.golden_goal,
+            _ => self.theme = (self.theme as i32 + delta).rem_euclid(THEMES.len() as i32) as usize,
         }
+    }
 
-        for y in 0..BUFFER_HEIGHT {
-            plot('|', BUFFER_WIDTH / 2, y, court_color);
+    fn cpu_move(&mut self) {
+        let mode = self.game_mode;
+        for seat in 0..MAX_PLAYERS {
+            if !mode.seat_active(self, seat) {
+                continue;
+            }
+            if let Some(cpu) = &mut self.cpus[seat] {
+                let motion = cpu.update(&self.ball, &self.players[seat]);
+                self.players[seat].shift(motion);
+            }
         }
     }
     
-    pub fn draw_hockey_rink(&self) {
-        let court_color = ColorCode::new(Color::Blue, Color::White);
-        let red_lines = ColorCode::new(Color::Red, Color::White);
-    
-        // Draw the outer boundary
-        for y in 0..BUFFER_HEIGHT {
-            plot('|', 0, y, court_color);
-            plot('|', BUFFER_WIDTH - 1, y, court_color);
-        }
-        for x in 0..BUFFER_WIDTH {
-            plot('-', x, 0, court_color);
-            plot('-', x, BUFFER_HEIGHT - 1, court_color);
-        }
-    
-        // Draw the vertical lines
-        let vertical_x1 = BUFFER_WIDTH / 4;
-        let vertical_x2 = BUFFER_WIDTH / 2;
-        let vertical_x3 = BUFFER_WIDTH * 3 / 4;
-    
-        for y in 0..BUFFER_HEIGHT {
-            plot('|', vertical_x1, y, court_color);
-            plot('|', vertical_x3, y, court_color);
-            plot('|', vertical_x2, y, red_lines);
+    /// Draws the current screen's menu, centered, together with the text that goes with it.
+    fn display_menu<S: Surface>(&self, screen: &mut S) {
+        let mut body: [TextLine; 6] = Default::default();
+        let mut body_lines = 0;
+        let mut values: [TextLine; SETTINGS_MENU.len()] = Default::default();
+        let mut value_lines = 0;
+        match self.game_state {
+            GameState::HowToPlay => {
+                let _ = body[0].write_str("Player 1 (LEFT) use W and S");
+                let _ = body[1].write_str("Player 2 (RIGHT) use Arrow Keys");
+                let _ = body[2].write_str("Breakout: A and D or LEFT and RIGHT");
+                let _ = body[3].write_str("Four Player: top J and L, bottom 4 and 6 on the keypad");
+                self.describe_scoring(&mut body[4]);
+                self.describe_timing(&mut body[5]);
+                body_lines = 6;
+            }
+            GameState::SeatSelect => {
+                for (value, cpu) in values.iter_mut().zip(self.cpu_seats) {
+                    let _ = value.write_str(if cpu { "CPU" } else { "Human" });
+                }
+                value_lines = MAX_PLAYERS;
+                let _ = body[0].write_str("LEFT/RIGHT to switch between human and CPU");
+                body_lines = 1;
+            }
+            GameState::Settings => {
+                let rules = self.rules;
+                let _ = write!(values[0], "{}", rules.target_score);
+                let _ = write!(values[1], "{}", rules.win_by);
+                let _ = match rules.time_limit {
+                    None if self.game_mode.period_length().is_some() => write!(values[2], "Default"),
+                    None => write!(values[2], "Off"),
+                    Some(seconds) => write!(values[2], "{}:{:02}", seconds / 60, seconds % 60),
+                };
+                let _ = write!(values[3], "{}", if rules.golden_goal { "On" } else { "Off" });
+                let _ = write!(values[4], "{}", self.theme().name);
+                value_lines = SETTINGS_MENU.len();
+                let _ = body[0].write_str("UP/DOWN to choose, LEFT/RIGHT to change, ENTER to return");
+                body_lines = 1;
+            }
+            GameState::Halftime => {
+                let _ = write!(body[0], "Player 1  {} - {}  Player 2", self.score1, self.score2);
+                let _ = body[1].write_str("Players switch ends");
+                body_lines = 2;
+            }
+            GameState::GameOver => {
+                self.game_mode.describe_result(self, &mut body[body_lines]);
+                if !body[body_lines].is_empty() {
+                    body_lines += 1;
+                }
+                self.describe_scoring(&mut body[body_lines]);
+                self.describe_timing(&mut body[body_lines + 1]);
+                body_lines += 2;
+            }
+            _ => {}
         }
+
+        let body = body.each_ref().map(|line| line.as_str());
+        let values = values.each_ref().map(|line| line.as_str());
+        let top = (BUFFER_HEIGHT - self.menu.height(body_lines)) / 2;
+        self.menu.render(screen, top, &body[..body_lines], &values[..value_lines], &self.theme().menu);
     }
-    
-    fn clear_screen(&self) {
-        for y in 0..BUFFER_HEIGHT {
-            for x in 0..BUFFER_WIDTH {
-                plot(' ', x, y, ColorCode::new(Color::Black, Color::Black));
-            }
+
+    /// Clears the screen to the field color and draws the current mode's markings.
+    fn draw_field<S: Surface>(&self, screen: &mut S) {
+        let palette = self.palette();
+        self.clear_screen_playing(screen, palette.field);
+        self.game_mode.draw_field(screen, &palette);
+    }
+
+    /// Draws the field, paddles, ball and HUD as they stand, without moving anything.
+    fn draw_frozen_frame<S: Surface>(&mut self, screen: &mut S) {
+        let palette = self.palette();
+        self.draw_field(screen);
+        self.render(screen);
+        self.ball.render(screen, palette.ball, palette.field);
+        if self.restart_ticks > 0 {
+            self.game_mode.draw_restart(self, screen);
         }
+        self.display_clock(screen, palette);
     }
 
-    
-    fn clear_screen_playing(&self, background_color: Color) {
-        
-        for y in 0..BUFFER_HEIGHT {
-            for x in 0..BUFFER_WIDTH {
-                plot(' ', x, y, ColorCode::new(Color::Black, background_color));
+    fn display_pause_menu<S: Surface>(&self, screen: &mut S) {
+        let width = 26;
+        let height = self.menu.height(0) + 2;
+        let left = (BUFFER_WIDTH - width) / 2;
+        let top = (BUFFER_HEIGHT - height) / 2;
+        let color = self.theme().menu.text();
+
+        for y in top..top + height {
+            for x in left..left + width {
+                let edge_x = x == left || x == left + width - 1;
+                let edge_y = y == top || y == top + height - 1;
+                let c = match (edge_x, edge_y) {
+                    (true, true) => '+',
+                    (true, false) => '|',
+                    (false, true) => '-',
+                    (false, false) => ' ',
+                };
+                screen.plot(c, x, y, color);
             }
         }
+        self.menu.render(screen, top + 1, &[], &[], &self.theme().menu);
     }
 
-    fn render(&mut self) {
-        let bg_color = match self.game_mode {
-            GameMode::Footy => Color::Green,
-            GameMode::Tennis => Color::Blue,
-            GameMode::Hockey => Color::White,
-        };
-        let p1_color = match self.game_mode {
-            GameMode::Footy => Color::Blue,
-            GameMode::Tennis => Color::Yellow,
-            GameMode::Hockey => Color::Blue,
-        };
-        self.player1.render(ColorCode::new(p1_color, bg_color));
-        self.player2.render(ColorCode::new(Color::Red, bg_color));  
-        self.display_score();  
+    fn clear_screen<S: Surface>(&self, screen: &mut S) {
+        let background = self.theme().menu.background;
+        screen.fill(ColorCode::new(background, background));
     }
+
     
-    fn handle_collisions(&mut self) {
-        let ball_x = self.ball.x as isize;
-        let ball_y = self.ball.y as isize;
-        let ball_x_velocity = self.ball.x_velocity;
-        let ball_y_velocity = self.ball.y_velocity;
+    fn clear_screen_playing<S: Surface>(&self, screen: &mut S, background_color: Color) {
+        screen.fill(ColorCode::new(Color::Black, background_color));
+    }
 
-        // Check for collision with top or bottom wall
-        if ball_y <= 0 || ball_y >= (BUFFER_HEIGHT - 1) as isize {
-            self.ball.change_direction(ball_x_velocity, -ball_y_velocity);
+    fn render<S: Surface>(&mut self, screen: &mut S) {
+        let palette = self.palette();
+        let mode = self.game_mode;
+        mode.draw_objects(self, screen);
+        for seat in 0..MAX_PLAYERS {
+            if mode.seat_active(self, seat) {
+                self.players[seat].render(screen, ColorCode::new(palette.player(seat), palette.field));
+            }
         }
+        mode.draw_score(self, screen);
+    }
 
-        // Check for collision with player 1
-        if ball_x == self.player1.x as isize && ball_y >= self.player1.y as isize && ball_y < (self.player1.y + PADDLE_HEIGHT) as isize {
-            self.ball.change_direction(ball_x_velocity.abs(), (ball_y - self.player1.y as isize).signum());
+    /// Moves the ball through one tick. A paddle contact is reported by seat.
+    fn step_ball(&mut self) -> Option<Contact> {
+        let mode = self.game_mode;
+        let arena = mode.arena(self);
+        let mut seats = [0; MAX_PLAYERS];
+        let mut players = 0;
+        for seat in 0..MAX_PLAYERS {
+            if mode.seat_active(self, seat) {
+                seats[players] = seat;
+                players += 1;
+            }
         }
-
-        // Check for collision with player 2
-        if ball_x == self.player2.x as isize && ball_y >= self.player2.y as isize && ball_y < (self.player2.y + PADDLE_HEIGHT) as isize {
-            self.ball.change_direction(-ball_x_velocity.abs(), (ball_y - self.player2.y as isize).signum());
+        let paddles = seats.map(|seat| self.players[seat].collider());
+        let mut blocks = [Block::EMPTY; MAX_BLOCKS];
+        let count = mode.blocks(self, &mut blocks);
+        match physics::step(&mut self.ball, &arena, &paddles[..players], &blocks[..count]) {
+            Some(Contact::Paddle(index)) => Some(Contact::Paddle(seats[index])),
+            contact => contact,
         }
-        
-        // Check for a point scored by player 1
-        if ball_x >= (BUFFER_WIDTH - 1) as isize {
-            self.score1 += 1;
-            if self.score1 == 7 {
-                self.game_state = GameState::GameOver;
+    }
+
+    fn handle_collisions(&mut self, contact: Option<Contact>) {
+        // Walls have already been handled by physics::step.
+        let max_speed = self.game_mode.max_ball_speed();
+
+        // Check for collision with any player, sending the ball back the way it came
+        if let Some(Contact::Paddle(index)) = contact {
+            let player = &self.players[index];
+            if player.is_horizontal() {
+                // Columns are about half as tall as rows, so the ball may cross twice as fast.
+                let rally_speed = (self.ball.y_velocity().abs() + SPEED_RAMP).min(max_speed);
+                let direction = -self.ball.y_velocity().signum();
+                let slope = player.deflection(self.ball.col()) * 2;
+                self.ball.change_direction(slope * rally_speed, rally_speed * direction);
             } else {
-                self.ball.reset(BUFFER_WIDTH / 2, BUFFER_HEIGHT / 2, -1, 1);
+                let rally_speed = (self.ball.speed() + SPEED_RAMP).min(max_speed);
+                let direction = -self.ball.x_velocity().signum();
+                self.ball.change_direction(rally_speed * direction, player.deflection(self.ball.row()) * rally_speed);
+                self.ball.add_spin(player.motion());
             }
+            let mode = self.game_mode;
+            mode.paddle_hit(self, index);
+        }
+
+        if let Some(contact) = contact {
+            let mode = self.game_mode;
+            mode.handle_contact(self, contact);
         }
+    }
 
-        // Check for a point scored by player 2
-        if ball_x <= 0 {
+    /// Scores a point for whoever defends the other end from **side**, then serves unless
+    /// that won the match.
+    fn score_goal(&mut self, side: Side) {
+        let scorer = 1 - self.defender(side);
+        if scorer == 0 {
+            self.score1 += 1;
+        } else {
             self.score2 += 1;
-            if self.score2 == 7 {
-                self.game_state = GameState::GameOver;
-            } else {
-                self.ball.reset(BUFFER_WIDTH / 2, BUFFER_HEIGHT / 2, 1, 1);
-            }
         }
+        let mode = self.game_mode;
+        mode.point_won(self, scorer);
+        if self.check_for_winner().is_none() {
+            self.serve(if side == Side::Right { -1 } else { 1 });
+        }
+    }
 
+    fn check_for_winner(&self) -> Option<Outcome> {
+        self.game_mode.outcome(self)
     }
 
-    fn display_score(&mut self) {
-        let score_color = match self.game_mode {
-            GameMode::Footy => Color::Green,
-            GameMode::Hockey => Color::White,
-            GameMode::Tennis => Color::Blue,
-        };
-        let p1_color = match self.game_mode {
-            GameMode::Footy => Color::Blue,
-            GameMode::Hockey => Color::Blue,
-            GameMode::Tennis => Color::Yellow,
-        };
-        plot_num(self.score1 as isize, 30, 1, ColorCode::new(p1_color, score_color));
-        plot_num(self.score2 as isize, 50, 1, ColorCode::new(Color::Red, score_color));
+    /// Writes a one-line summary of how the current mode's match is won.
+    fn describe_scoring(&self, line: &mut TextLine) {
+        self.game_mode.describe_scoring(self, line);
     }
 
-    fn check_for_winner(&self) -> u8 {
-        if self.score1 >= 7 {
-            1
-        } else if self.score2 >= 7 {
-            2
-        } else {
-            0
+    /// The length of the match in seconds: the rules' time limit, or else the current mode's
+    /// own halves or periods. None for an untimed match.
+    fn match_length(&self) -> Option<u32> {
+        let mode = self.game_mode;
+        self.rules.time_limit.or_else(|| mode.period_length().map(|seconds| seconds * mode.periods()))
+    }
+
+    /// Writes a one-line summary of the time limit, and how it is split into halves or periods.
+    fn describe_timing(&self, line: &mut TextLine) {
+        let rules = MatchRules { time_limit: self.match_length(), ..self.rules };
+        rules.describe_timing(line);
+        let periods = self.game_mode.periods();
+        if rules.time_limit.is_some() && periods > 1 {
+            let (_, name) = self.game_mode.period_names();
+            let _ = write!(line, " ({} {})", periods, name);
         }
     }
 
-    fn display_winner_message(&self, winner: u8) {
-        let mut winner_message;
-        let mut wm_color;
-        if self.score1 > self.score2 {
-            winner_message = "Player 1 WINS!";
-            wm_color = ColorCode::new(Color::Yellow, Color::Black);
+    /// Shows the match clock, e.g. "H2 03:15", or GOLDEN GOAL once time is up on a tied match.
+    fn display_clock<S: Surface>(&self, screen: &mut S, palette: Palette) {
+        let mut line = TextLine::new();
+        if self.clock.time_up() {
+            let _ = write!(line, "GOLDEN GOAL");
         } else {
-            winner_message = "Player 2 WINS!";
-            wm_color = ColorCode::new(Color::Yellow, Color::Black);
+            if self.clock.periods() > 1 && self.clock.is_timed() {
+                let (prefix, _) = self.game_mode.period_names();
+                let _ = write!(line, "{}{} ", prefix, self.clock.period());
+            }
+            self.clock.write_time(&mut line);
         }
-        let message_x = (BUFFER_WIDTH / 2).saturating_sub(winner_message.len() / 2);
-        let message_y = (BUFFER_HEIGHT / 2) - 2;
-        let color = ColorCode::new(Color::White, Color::Black);
-        plot_str(&winner_message, message_x, message_y, wm_color);
-    
-        let main_menu_message = "[M]ain Menu";
-        let main_menu_x = (BUFFER_WIDTH / 2).saturating_sub(main_menu_message.len() / 2);
-        let main_menu_y = message_y + 2;
-        plot_str(main_menu_message, main_menu_x, main_menu_y, color);
-
-        let restart_message = "[R]estart";
-        let restart_x = (BUFFER_WIDTH / 2).saturating_sub(restart_message.len() / 2);
-        let restart_y = main_menu_y + 2;
-        plot_str(restart_message, restart_x, restart_y, color);
-    }
-
-    fn draw_how_to_play(&mut self) {
-        let title = "How to Play:";
-        let p1msg = "Player 1 (LEFT) use W and S";
-        let p2msg = "Player 2 (RIGHT) use Arrow Keys";
-        let goal = "First to 7 points wins!";
-        let rturn = "Press H to Exit";
-        let color = ColorCode::new(Color::White, Color::Black);
-
-        let message_x = (BUFFER_WIDTH / 2).saturating_sub(title.len() / 2);
-        let message_y = (BUFFER_HEIGHT / 2) - 2;
-        plot_str(title, message_x, message_y, ColorCode::new(Color::Yellow, Color::Black));
-        
-        let p1_x = (BUFFER_WIDTH / 2).saturating_sub(p1msg.len() / 2);
-        let p1_y = message_y + 2;
-        plot_str(p1msg, p1_x, p1_y, color);
-        let p2_x = (BUFFER_WIDTH / 2).saturating_sub(p2msg.len() / 2);
-        let p2_y = p1_y + 1;
-        plot_str(p2msg, p2_x, p2_y, color);
-        let g_x = (BUFFER_WIDTH / 2).saturating_sub(goal.len() / 2);
-        let g_y = p2_y + 1;
-        plot_str(goal, g_x, g_y, color);
-        let r_x = (BUFFER_WIDTH / 2).saturating_sub(rturn.len() / 2);
-        let r_y = g_y + 2;
-        plot_str(rturn, r_x, r_y, color);
-
+        let y = self.game_mode.clock_row();
+        screen.plot_centered(line.as_str(), y, ColorCode::new(palette.hud, palette.field));
     }
 
     
     fn restart_game(&mut self) {
-        if let GameState::GameOver = self.game_state {
-            self.player1.y = BUFFER_HEIGHT / 2 - PADDLE_HEIGHT / 2;
-            self.player2.y = BUFFER_HEIGHT / 2 - PADDLE_HEIGHT / 2;
-            self.ball.reset(BUFFER_WIDTH / 2, BUFFER_HEIGHT / 2, 1, 1);
+        if let GameState::GameOver | GameState::Paused = self.game_state {
             self.score1 = 0;
             self.score2 = 0;
-            self.game_state = GameState::Playing;
+            self.outcome = None;
+            self.start_match();
+            self.set_state(GameState::Playing);
         }
     }
     
     fn go_main_menu(&mut self) {
-        if let GameState::GameOver = self.game_state {
-            self.player1.y = BUFFER_HEIGHT / 2 - PADDLE_HEIGHT / 2;
-            self.player2.y = BUFFER_HEIGHT / 2 - PADDLE_HEIGHT / 2;
-            self.ball.reset(BUFFER_WIDTH / 2, BUFFER_HEIGHT / 2, 1, 1);
+        if let GameState::GameOver | GameState::Paused = self.game_state {
             self.score1 = 0;
             self.score2 = 0;
-            self.game_state = GameState::MainMenu;
+            self.outcome = None;
+            self.start_match();
+            self.set_state(GameState::MainMenu);
         }
     }
 
 }
 
-// #[derive(Copy, Clone)]
-
 pub struct Player {
     pub x: usize,
     pub y: usize,
+    prev_x: usize,
     prev_y: usize,
-    max_velocity: usize,
+    length: usize,
+    horizontal: bool,
 }
 
 impl Player {
 
-    pub fn new(x: usize, y: usize, max_velocity: usize) -> Self {
-        Self { x, y, prev_y: y, max_velocity }
+    /// A paddle PADDLE_HEIGHT rows tall, with its top at (**x**, **y**).
+    pub fn new(x: usize, y: usize) -> Self {
+        Self { x, y, prev_x: x, prev_y: y, length: PADDLE_HEIGHT, horizontal: false }
+    }
+
+    /// A paddle lying along row **y**, **length** columns wide with its left end at **x**.
+    pub fn horizontal(x: usize, y: usize, length: usize) -> Self {
+        Self { x, y, prev_x: x, prev_y: y, length, horizontal: true }
+    }
+
+    pub fn is_horizontal(&self) -> bool {
+        self.horizontal
+    }
+
+    /// Cells covered by the paddle.
+    pub fn length(&self) -> usize {
+        self.length
     }
 
     pub fn move_up(&mut self) {
@@ -630,85 +888,388 @@ impl Player {
     }
 
     pub fn move_down(&mut self) {
-        if self.y < BUFFER_HEIGHT - PADDLE_HEIGHT {
+        if self.y < BUFFER_HEIGHT - self.length {
             self.y += 1;
         }
     }
 
-    pub fn render(&mut self, color: ColorCode) {
-        for y_offset in 0..PADDLE_HEIGHT {
-            plot(' ', self.x, self.prev_y + y_offset, ColorCode::new(color.foreground(), color.background()));
+    pub fn move_left(&mut self) {
+        if self.x > 0 {
+            self.x -= 1;
+        }
+    }
+
+    pub fn move_right(&mut self) {
+        if self.x < BUFFER_WIDTH - self.length {
+            self.x += 1;
+        }
+    }
+
+    /// Moves the paddle by **cells** along its length, negative being up or left, stopping at
+    /// the edges.
+    pub fn shift(&mut self, cells: isize) {
+        for _ in 0..cells.unsigned_abs() {
+            match (self.horizontal, cells < 0) {
+                (false, true) => self.move_up(),
+                (false, false) => self.move_down(),
+                (true, true) => self.move_left(),
+                (true, false) => self.move_right(),
+            }
+        }
+    }
+
+    pub fn render<S: Surface>(&mut self, screen: &mut S, color: ColorCode) {
+        for offset in 0..self.length {
+            let (x, y) = self.cell(self.prev_x, self.prev_y, offset);
+            screen.plot(' ', x, y, ColorCode::new(color.foreground(), color.background()));
         }
-        for y_offset in 0..PADDLE_HEIGHT {
-            plot('#', self.x, self.y + y_offset, color);
+        for offset in 0..self.length {
+            let (x, y) = self.cell(self.x, self.y, offset);
+            screen.plot('#', x, y, color);
+        }
+    }
+
+    // The cell **offset** along a paddle starting at (**x**, **y**).
+    fn cell(&self, x: usize, y: usize, offset: usize) -> (usize, usize) {
+        if self.horizontal { (x + offset, y) } else { (x, y + offset) }
+    }
+
+    /// Cells moved along the paddle's length since the last **settle()**, negative being up or left.
+    pub fn motion(&self) -> isize {
+        if self.horizontal {
+            self.x as isize - self.prev_x as isize
+        } else {
+            self.y as isize - self.prev_y as isize
         }
+    }
+
+    /// Marks the end of a tick, so that **motion()** starts counting from here.
+    pub fn settle(&mut self) {
+        self.prev_x = self.x;
         self.prev_y = self.y;
     }
 
+    pub fn collider(&self) -> physics::Paddle {
+        physics::Paddle { col: self.x, row: self.y, length: self.length, horizontal: self.horizontal }
+    }
+
+    /// The slope given to a ball that strikes this paddle at **pos**: its row, or its column
+    /// for a horizontal paddle. The slope is in rows per column, or columns per row.
+    pub fn deflection(&self, pos: usize) -> Fixed {
+        let start = if self.horizontal { self.x } else { self.y };
+        let offset = pos.saturating_sub(start).min(self.length - 1);
+        PADDLE_ZONES[offset * PADDLE_ZONES.len() / self.length]
+    }
+
 }
 
 pub struct Ball {
-    pub x: usize,
-    pub y: usize,
-    pub x_velocity: isize,
-    pub y_velocity: isize,
+    x: Fixed,
+    y: Fixed,
+    x_velocity: Fixed,
+    y_velocity: Fixed,
     prev_x: usize,
     prev_y: usize,
+    spin: Fixed,
+    spin_ticks: usize,
 }
 
 impl Ball {
-    pub fn new(x: usize, y: usize, x_velocity: isize, y_velocity: isize) -> Self {
-        Self { x, y, x_velocity, y_velocity, prev_x: x, prev_y: y}
+    /// A ball at the given cell, moving diagonally in the directions given by **x_dir** and
+    /// **y_dir** with a horizontal speed of **speed** cells per tick.
+    pub fn new(col: usize, row: usize, x_dir: isize, y_dir: isize, speed: Fixed) -> Self {
+        let mut ball = Self {
+            x: Fixed::ZERO, y: Fixed::ZERO, x_velocity: Fixed::ZERO, y_velocity: Fixed::ZERO,
+            prev_x: col, prev_y: row, spin: Fixed::ZERO, spin_ticks: 0,
+        };
+        ball.reset(col, row, x_dir, y_dir, speed);
+        ball
     }
 
-    pub fn update_position(&mut self, ball_color: Color, bgcolor: Color) {
-        plot(' ', self.prev_x, self.prev_y, ColorCode::new(Color::Black, bgcolor));
-        
-        self.x = (self.x as isize + self.x_velocity) as usize;
-        self.y = (self.y as isize + self.y_velocity) as usize;
-        plot('@', self.x, self.y, ColorCode::new(ball_color, bgcolor));
+    /// The column the ball is drawn in.
+    pub fn col(&self) -> usize {
+        self.x.round().clamp(0, BUFFER_WIDTH as i32 - 1) as usize
+    }
 
-        self.prev_x = self.x;
-        self.prev_y = self.y;
+    /// The row the ball is drawn in.
+    pub fn row(&self) -> usize {
+        self.y.round().clamp(0, BUFFER_HEIGHT as i32 - 1) as usize
     }
 
-    pub fn change_direction(&mut self, x_velocity: isize, y_velocity: isize) {
-        self.x_velocity = x_velocity;
-        self.y_velocity = y_velocity;
+    pub fn x(&self) -> Fixed {
+        self.x
+    }
+
+    pub fn y(&self) -> Fixed {
+        self.y
+    }
+
+    pub fn x_velocity(&self) -> Fixed {
+        self.x_velocity
+    }
+
+    pub fn y_velocity(&self) -> Fixed {
+        self.y_velocity
+    }
+
+    /// Horizontal speed, in cells per tick.
+    pub fn speed(&self) -> Fixed {
+        self.x_velocity.abs()
+    }
+
+    /// Spin from a paddle moving **paddle_motion** rows as it strikes the ball. The ball
+    /// curves in the direction the paddle was moving over the next few ticks.
+    pub fn add_spin(&mut self, paddle_motion: isize) {
+        let strength = paddle_motion.clamp(-MAX_SPIN, MAX_SPIN) as i32;
+        self.spin = SPIN_ACCELERATION * strength;
+        self.spin_ticks = if strength == 0 { 0 } else { SPIN_DURATION };
+    }
+
+    pub fn spin(&self) -> Fixed {
+        self.spin
     }
 
-    pub fn reset(&mut self, x: usize, y: usize, x_velocity: isize, y_velocity: isize) {
+    pub(crate) fn apply_spin(&mut self) {
+        if self.spin_ticks == 0 {
+            return;
+        }
+        self.spin_ticks -= 1;
+        self.y_velocity = (self.y_velocity + self.spin).clamp(-MAX_Y_VELOCITY, MAX_Y_VELOCITY);
+        if self.spin_ticks == 0 {
+            self.spin = Fixed::ZERO;
+        }
+    }
+
+    /// Sets the position and velocity that physics::step() arrived at.
+    pub(crate) fn place(&mut self, x: Fixed, y: Fixed, x_velocity: Fixed, y_velocity: Fixed) {
         self.x = x;
         self.y = y;
         self.x_velocity = x_velocity;
         self.y_velocity = y_velocity;
     }
 
+    pub fn render<S: Surface>(&mut self, screen: &mut S, ball_color: Color, bgcolor: Color) {
+        screen.plot(' ', self.prev_x, self.prev_y, ColorCode::new(Color::Black, bgcolor));
+        screen.plot('@', self.col(), self.row(), ColorCode::new(ball_color, bgcolor));
+
+        self.prev_x = self.col();
+        self.prev_y = self.row();
+    }
+
+    pub fn change_direction(&mut self, x_velocity: Fixed, y_velocity: Fixed) {
+        self.x_velocity = x_velocity;
+        self.y_velocity = y_velocity.clamp(-MAX_Y_VELOCITY, MAX_Y_VELOCITY);
+    }
+
+    pub fn reset(&mut self, col: usize, row: usize, x_dir: isize, y_dir: isize, speed: Fixed) {
+        self.x = Fixed::from_int(col as i32);
+        self.y = Fixed::from_int(row as i32);
+        self.x_velocity = speed * x_dir.signum() as i32;
+        self.y_velocity = speed * y_dir.signum() as i32;
+        self.spin = Fixed::ZERO;
+        self.spin_ticks = 0;
+    }
+
 }
 
-// Press ENTER to start
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::harness::{Simulation, Step};
+
+    #[test]
+    fn snapshot_describes_play_for_crash_reports() {
+        let mut game = Game::new();
+        game.select_difficulty(Difficulty::Medium);
+        game.ball.place(Fixed::from_int(40), Fixed::from_ratio(25, 2), Fixed::from_ratio(3, 4), -Fixed::HALF);
+        let snapshot = game.snapshot();
+        assert_eq!(snapshot.state, GameState::Playing);
+        assert_eq!(snapshot.paddles[2], None);
+        let text = format!("{}", snapshot);
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines[0], "Game: Playing in Footy (Medium), tick 0");
+        assert_eq!(lines[1], "Score: 0 - 0");
+        assert_eq!(lines[2], "Ball: at (40.00, 12.50) moving (0.75, -0.50)");
+        let (p1, p2) = (game.player1(), game.player2());
+        assert_eq!(lines[3], format!("Paddles:  P1 ({}, {})  P2 ({}, {})", p1.x, p1.y, p2.x, p2.y));
+    }
+
+    #[test]
+    fn main_menu_is_drawn() {
+        let mut sim = Simulation::new();
+        sim.ticks(1);
+        assert_eq!(sim.game().state(), GameState::MainMenu);
+        assert!(sim.screen_contains("FOOTY-PONG"));
+        assert!(sim.screen_contains("Start Game"));
+    }
+
+    #[test]
+    fn menus_lead_to_playing() {
+        let mut sim = Simulation::new();
+        sim.run(&[Step::Ticks(1), Step::raw(KeyCode::Enter), Step::Ticks(1)]);
+        assert_eq!(sim.game().state(), GameState::SelectGameMode);
+        assert!(sim.screen_contains("Select Game Mode:"));
+
+        sim.run(&[Step::char('h'), Step::Ticks(1)]);
+        assert_eq!(sim.game().state(), GameState::DifficultySelect);
+        assert_eq!(sim.game().mode().name(), "Hockey");
+        assert!(sim.screen_contains("Select Difficulty:"));
+
+        sim.run(&[Step::char('2'), Step::Ticks(1)]);
+        assert_eq!(sim.game().state(), GameState::Playing);
+        assert_eq!(sim.game().difficulty(), Difficulty::Medium);
+    }
+
+    #[test]
+    fn how_to_play_toggles() {
+        let mut sim = Simulation::new();
+        sim.run(&[Step::char('h'), Step::Ticks(1)]);
+        assert_eq!(sim.game().state(), GameState::HowToPlay);
+        assert!(sim.screen_contains("First to 7 points wins!"));
+        sim.run(&[Step::char('h'), Step::Ticks(1)]);
+        assert_eq!(sim.game().state(), GameState::MainMenu);
+    }
+
+    #[test]
+    fn ball_and_paddles_are_rendered() {
+        let mut sim = Simulation::start_match('f', '0');
+        sim.ticks(1);
+        let ball = sim.game().ball();
+        assert_eq!(sim.screen().cell(ball.col(), ball.row()).ch, '@');
+        let player1 = sim.game().player1();
+        assert_eq!(sim.screen().cell(player1.x, player1.y).ch, '#');
+    }
+
+    #[test]
+    fn paddles_follow_key_presses() {
+        let mut sim = Simulation::start_match('t', '0');
+        let start = sim.game().player1().y;
+        sim.run(&[Step::char('w'), Step::char('w'), Step::Ticks(1)]);
+        assert_eq!(sim.game().player1().y, start - 2);
+        sim.ticks(3);
+        assert_eq!(sim.game().player1().y, start - 2);
+        let start = sim.game().player2().y;
+        sim.run(&[Step::raw(KeyCode::ArrowDown), Step::Ticks(1)]);
+        assert_eq!(sim.game().player2().y, start + 1);
+    }
+
+    #[test]
+    fn held_keys_move_both_paddles() {
+        let mut sim = Simulation::start_match('f', '0');
+        let start1 = sim.game().player1().y;
+        let start2 = sim.game().player2().y;
+        sim.run(&[Step::press(KeyCode::W), Step::press(KeyCode::ArrowDown), Step::Ticks(3)]);
+        assert_eq!(sim.game().player1().y, start1 - 3);
+        assert_eq!(sim.game().player2().y, start2 + 3);
+        sim.run(&[Step::release(KeyCode::W), Step::Ticks(2)]);
+        assert_eq!(sim.game().player1().y, start1 - 3);
+        assert_eq!(sim.game().player2().y, start2 + 5);
+    }
 
-// -------------------------------
+    #[test]
+    fn points_are_scored() {
+        let mut sim = Simulation::start_match('f', '0');
+        assert!(sim.run_until(1000, |game| game.scores() != (0, 0)));
+        let (score1, score2) = sim.game().scores();
+        assert_eq!(score1 + score2, 1);
+    }
 
-// Select Game Mode:
-//
-// [F]ooty (Classic mode)
-// [H]ockey
-// [T]ennis
+    #[test]
+    fn match_ends_at_seven() {
+        let mut sim = Simulation::start_match('f', '0');
+        assert!(sim.run_until(20_000, |game| game.state() == GameState::GameOver));
+        let (score1, score2) = sim.game().scores();
+        assert_eq!(score1.max(score2), 7);
+        sim.ticks(1);
+        assert!(sim.screen_contains("WINS!"));
+        sim.run(&[Step::char('m'), Step::Ticks(1)]);
+        assert_eq!(sim.game().state(), GameState::MainMenu);
+        assert_eq!(sim.game().scores(), (0, 0));
+    }
 
-// -------------------------------
+    #[test]
+    fn settings_change_the_target_score() {
+        let mut sim = Simulation::new();
+        sim.run(&[Step::char('s'), Step::Ticks(1)]);
+        assert_eq!(sim.game().state(), GameState::Settings);
+        assert!(sim.screen_contains("Match Settings:"));
+        sim.run(&[
+            Step::raw(KeyCode::ArrowLeft),
+            Step::raw(KeyCode::ArrowLeft),
+            Step::raw(KeyCode::ArrowLeft),
+            Step::raw(KeyCode::ArrowLeft),
+            Step::raw(KeyCode::Escape),
+            Step::char('h'),
+            Step::Ticks(1),
+        ]);
+        assert_eq!(sim.game().rules().target_score, 3);
+        assert!(sim.screen_contains("First to 3 points wins!"));
+
+        sim.run(&[Step::char('h'), Step::Ticks(1), Step::raw(KeyCode::Enter), Step::Ticks(1), Step::char('f'), Step::char('0')]);
+        assert!(sim.run_until(20_000, |game| game.state() == GameState::GameOver));
+        let (score1, score2) = sim.game().scores();
+        assert_eq!(score1.max(score2), 3);
+    }
 
-// Select Difficulty:
-//
-// [0] Multiplayer 
-// [1] Easy
-// [2] Medium
-// [3] Hard
+    #[test]
+    fn time_limit_can_end_in_a_draw() {
+        let mut sim = Simulation::new();
+        let mut rules = sim.game().rules();
+        rules.time_limit = Some(60);
+        rules.golden_goal = false;
+        sim.game_mut().set_rules(rules);
+        sim.run(&[Step::raw(KeyCode::Enter), Step::char('f'), Step::char('0')]);
+        assert!(sim.run_until(10_000, |game| game.state() == GameState::Halftime));
+        assert_eq!(sim.game().clock().period(), 1);
+        sim.ticks(1);
+        assert!(sim.screen_contains("HALF TIME"));
+        let player1 = sim.game().player1().x;
+        sim.run(&[Step::raw(KeyCode::Enter), Step::Ticks(1)]);
+        assert_eq!(sim.game().state(), GameState::Playing);
+        assert_eq!(sim.game().player2().x, player1);
+        assert!(sim.screen_contains("H2 00:30"));
+        assert!(sim.run_until(10_000, |game| game.state() == GameState::GameOver));
+        let (score1, score2) = sim.game().scores();
+        let expected = match score1.cmp(&score2) {
+            core::cmp::Ordering::Greater => Outcome::Player1,
+            core::cmp::Ordering::Less => Outcome::Player2,
+            core::cmp::Ordering::Equal => Outcome::Draw,
+        };
+        assert_eq!(sim.game().outcome(), Some(expected));
+        assert!(sim.screen_contains("Time limit 1:00, draw if tied (2 halves)"));
+    }
 
-// -------------------------------
+    #[test]
+    fn pause_freezes_play_until_resumed() {
+        let mut sim = Simulation::start_match('f', '0');
+        sim.ticks(20);
+        let (col, row) = (sim.game().ball().col(), sim.game().ball().row());
+        let mut clock = TextLine::new();
+        sim.game().clock().write_time(&mut clock);
+        sim.run(&[Step::char(' '), Step::Ticks(30)]);
+        assert_eq!(sim.game().state(), GameState::Paused);
+        assert!(sim.screen_contains("PAUSED"));
+        assert!(sim.screen_contains("[Q]uit to main menu"));
+        assert_eq!((sim.game().ball().col(), sim.game().ball().row()), (col, row));
+
+        sim.run(&[Step::char(' '), Step::Ticks(1)]);
+        assert_eq!(sim.game().state(), GameState::Playing);
+        assert!(!sim.screen_contains("PAUSED"));
+        assert_eq!(sim.screen().cell(col, row).ch, '@');
+        assert!(sim.screen_contains(clock.as_str()));
+    }
 
-// How to Play:
-//
-// Player 1 (LEFT) use W and S
-// Player 2 (RIGHT) use Arrow Keys
-// First to 7 points wins!
+    #[test]
+    fn pause_menu_restarts_and_quits() {
+        let mut sim = Simulation::start_match('f', '0');
+        assert!(sim.run_until(1000, |game| game.scores() != (0, 0)));
+        sim.run(&[Step::raw(KeyCode::Escape), Step::raw(KeyCode::ArrowDown), Step::raw(KeyCode::Enter)]);
+        assert_eq!(sim.game().state(), GameState::Playing);
+        assert_eq!(sim.game().scores(), (0, 0));
+
+        sim.run(&[Step::char(' '), Step::char('s'), Step::Ticks(1)]);
+        assert_eq!(sim.game().state(), GameState::Settings);
+        sim.run(&[Step::raw(KeyCode::Escape), Step::char('q'), Step::Ticks(1)]);
+        assert_eq!(sim.game().state(), GameState::MainMenu);
+    }
+}
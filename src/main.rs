@@ -1,37 +1,71 @@
 #![no_std]
 #![no_main]
 
-use pc_keyboard::DecodedKey;
-use pluggable_interrupt_os::HandlerTable;
-use BareMetalGame::Game;
+use core::fmt::Write;
+use pc_keyboard::{DecodedKey, KeyEvent};
+use BareMetalGame::{Game, Snapshot};
+use BareMetalGame::ansi::AnsiInput;
+use BareMetalGame::display::DoubleBuffer;
+use BareMetalGame::input::KeyQueue;
+use BareMetalGame::platform::HandlerTable;
+#[cfg(feature = "serial-screen")]
+use BareMetalGame::platform::{interrupts::irq, serial};
+use BareMetalGame::platform::vga_buffer::clear_screen;
 use crossbeam::atomic::AtomicCell;
-use pluggable_interrupt_os::vga_buffer::clear_screen;
+use spin::Mutex;
 
 #[no_mangle]
 pub extern "C" fn _start() -> ! {
-    HandlerTable::new()
+    let handlers = HandlerTable::new()
         .keyboard(key)
-        .timer(tick)
+        .raw_keyboard(key_event)
+        .timer(tick);
+    // Terminal input only matters when the screen is mirrored to the serial port.
+    #[cfg(feature = "serial-screen")]
+    let handlers = handlers.irq(irq::COM1, serial_input);
+    handlers
         .startup(startup)
         .cpu_loop(cpu_loop)
+        .crash_info(crash_info)
         .start()
 }
  
-static LAST_KEY: AtomicCell<Option<DecodedKey>> = AtomicCell::new(None);
+static KEYS: KeyQueue<DecodedKey, 64> = KeyQueue::new();
+static KEY_EVENTS: KeyQueue<KeyEvent, 64> = KeyQueue::new();
+// Bytes typed on a terminal attached to the serial port.
+static SERIAL_BYTES: KeyQueue<u8, 64> = KeyQueue::new();
 static TICKS: AtomicCell<usize> = AtomicCell::new(0);
+// The game as of the last tick, for crash reports.
+static SNAPSHOT: Mutex<Option<Snapshot>> = Mutex::new(None);
 
 fn cpu_loop() -> ! {
     let mut kernel = Game::new();
+    let mut screen = DoubleBuffer::new();
+    // Build with --features serial-screen to play from a terminal, e.g. under -nographic.
+    screen.set_serial_mirror(cfg!(feature = "serial-screen"));
+    let mut terminal = AnsiInput::new();
     let mut last_tick = 0;
     loop {
-        if let Some(key) = LAST_KEY.load() {
-            LAST_KEY.store(None);
+        while let Some(event) = KEY_EVENTS.pop() {
+            kernel.key_event(&event);
+        }
+        while let Some(key) = KEYS.pop() {
             kernel.key(key);
         }
+        while let Some(byte) = SERIAL_BYTES.pop() {
+            if let Some(key) = terminal.push(byte) {
+                kernel.terminal_key(key);
+            }
+        }
         let current_tick = TICKS.load();
         if current_tick > last_tick {
             last_tick = current_tick;
-            kernel.tick();
+            if let Some(key) = terminal.tick() {
+                kernel.terminal_key(key);
+            }
+            kernel.tick(&mut screen);
+            screen.flush();
+            *SNAPSHOT.lock() = Some(kernel.snapshot());
         }
     }
 }
@@ -42,7 +76,27 @@ fn tick() {
 
 fn key(key: DecodedKey) {
     // panic!("{key:?}");
-    LAST_KEY.store(Some(key));
+    KEYS.push(key);
+}
+
+fn key_event(event: KeyEvent) {
+    KEY_EVENTS.push(event);
+}
+
+#[cfg(feature = "serial-screen")]
+fn serial_input() {
+    while let Some(byte) = serial::try_receive() {
+        SERIAL_BYTES.push(byte);
+    }
+}
+
+fn crash_info(out: &mut dyn Write) {
+    match SNAPSHOT.try_lock().and_then(|snapshot| *snapshot) {
+        Some(snapshot) => { let _ = write!(out, "{}", snapshot); }
+        None => { let _ = writeln!(out, "Game state unavailable"); }
+    }
+    let _ = writeln!(out, "Dropped input: {} keys, {} key events, {} serial bytes",
+        KEYS.overflows(), KEY_EVENTS.overflows(), SERIAL_BYTES.overflows());
 }
 
 fn startup() {
@@ -0,0 +1,132 @@
+// Keyboard-driven menus.
+//
+// A Menu is a title over a list of items. The arrow keys move a highlighted selection, Enter
+// confirms it and Escape backs out; an item may also have a hotkey that selects and confirms
+// it in one press. A menu only reports what the player did, leaving the response to Game.
+// It keeps its own copy of the items, so a list built at run time, such as the game modes,
+// works as well as a constant one.
+
+use core::fmt::Write;
+use pc_keyboard::{DecodedKey, KeyCode};
+use crate::platform::vga_buffer::BUFFER_WIDTH;
+use crate::display::{Surface, TextLine};
+use crate::theme::MenuColors;
+
+/// The most items a menu can hold.
+pub const MAX_MENU_ITEMS: usize = 8;
+
+/// One selectable line of a menu.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MenuItem {
+    pub label: &'static str,
+    pub hotkey: Option<char>,
+}
+
+impl MenuItem {
+    pub const fn new(label: &'static str, hotkey: Option<char>) -> Self {
+        Self { label, hotkey }
+    }
+}
+
+/// What a key press asked the menu to do.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MenuAction {
+    /// The item at this index was chosen, with Enter or its hotkey.
+    Confirm(usize),
+    /// Left (-1) or right (+1) was pressed on the item at this index.
+    Adjust(usize, i32),
+    /// Escape was pressed.
+    Back,
+}
+
+pub struct Menu {
+    title: &'static str,
+    items: [MenuItem; MAX_MENU_ITEMS],
+    len: usize,
+    selected: usize,
+}
+
+impl Menu {
+    /// A menu listing **items** under **title**. Items past MAX_MENU_ITEMS are left off.
+    pub fn new(title: &'static str, items: &[MenuItem]) -> Self {
+        let len = items.len().min(MAX_MENU_ITEMS);
+        let mut menu = Self { title, items: [MenuItem::new("", None); MAX_MENU_ITEMS], len, selected: 0 };
+        menu.items[..len].copy_from_slice(&items[..len]);
+        menu
+    }
+
+    fn items(&self) -> &[MenuItem] {
+        &self.items[..self.len]
+    }
+
+    pub fn select(&mut self, index: usize) {
+        if index < self.len {
+            self.selected = index;
+        }
+    }
+
+    /// Handles **key**, returning what the player asked for, if anything.
+    pub fn handle_key(&mut self, key: DecodedKey) -> Option<MenuAction> {
+        let count = self.len;
+        match key {
+            DecodedKey::RawKey(KeyCode::ArrowUp) if count > 0 => {
+                self.selected = (self.selected + count - 1) % count;
+                None
+            }
+            DecodedKey::RawKey(KeyCode::ArrowDown) if count > 0 => {
+                self.selected = (self.selected + 1) % count;
+                None
+            }
+            DecodedKey::RawKey(KeyCode::ArrowLeft) if count > 0 => Some(MenuAction::Adjust(self.selected, -1)),
+            DecodedKey::RawKey(KeyCode::ArrowRight) if count > 0 => Some(MenuAction::Adjust(self.selected, 1)),
+            DecodedKey::RawKey(KeyCode::Enter) | DecodedKey::Unicode('\n') | DecodedKey::Unicode('\r') => {
+                (count > 0).then_some(MenuAction::Confirm(self.selected))
+            }
+            DecodedKey::RawKey(KeyCode::Escape) | DecodedKey::Unicode('\u{1b}') => Some(MenuAction::Back),
+            DecodedKey::Unicode(c) => {
+                let c = c.to_ascii_lowercase();
+                let index = self.items().iter().position(|item| item.hotkey == Some(c))?;
+                self.selected = index;
+                Some(MenuAction::Confirm(index))
+            }
+            _ => None,
+        }
+    }
+
+    /// Rows taken up by **render** with **body_lines** lines of text under the title.
+    pub fn height(&self, body_lines: usize) -> usize {
+        let body = if body_lines > 0 { body_lines + 1 } else { 0 };
+        2 + body + self.len
+    }
+
+    /// Draws the title on **row**, then any **body** text, then the items as a centered
+    /// block with the selection highlighted. When **values** are given, each item is followed
+    /// by its value, as on the settings screen. Everything is drawn in **colors**.
+    pub fn render<S: Surface>(&self, screen: &mut S, row: usize, body: &[&str], values: &[&str], colors: &MenuColors) {
+        let color = colors.text();
+        let selected = colors.selected();
+        screen.plot_centered(self.title, row, colors.title());
+
+        let mut y = row + 2;
+        for line in body {
+            screen.plot_centered(line, y, color);
+            y += 1;
+        }
+        if !body.is_empty() {
+            y += 1;
+        }
+
+        let label_width = self.items().iter().map(|item| item.label.len()).max().unwrap_or(0);
+        let value_width = values.iter().map(|value| value.len()).max().unwrap_or(0);
+        for (i, item) in self.items().iter().enumerate() {
+            let mut line = TextLine::new();
+            let _ = match values.get(i) {
+                Some(value) => write!(line, "{:<w$}  < {:>v$} >", item.label, value, w = label_width, v = value_width),
+                None => write!(line, "{:<w$}", item.label, w = label_width),
+            };
+            let x = (BUFFER_WIDTH / 2).saturating_sub(line.len() / 2);
+            screen.plot_str(line.as_str(), x, y, if i == self.selected { selected } else { color });
+            y += 1;
+        }
+    }
+}
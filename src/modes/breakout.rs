@@ -0,0 +1,374 @@
+// Breakout.
+//
+// One player keeps the ball in play with a paddle along the bottom row and breaks down a
+// wall of bricks. Each brick takes as many hits as its digit in the level layout; the ball
+// bounces off bricks, walls and the paddle through the same physics as the pong modes.
+// Missing the ball costs a life. Clearing a wall moves on to the next level, and clearing
+// the last level wins.
+
+use core::fmt::Write;
+use crate::platform::vga_buffer::{BUFFER_WIDTH, BUFFER_HEIGHT, ColorCode, Color};
+use crate::display::{Surface, TextLine};
+use crate::fixed::Fixed;
+use crate::menu::MenuItem;
+use crate::physics::{Arena, Block, Contact, EndLine};
+use crate::rules::Outcome;
+use crate::theme::Palette;
+use crate::{Game, Player, MAX_PLAYERS};
+use super::{GameMode, ModeState};
+
+pub const START_LIVES: u32 = 3;
+const PADDLE_WIDTH: usize = 9;
+// Bricks are BRICK_WIDTH columns wide, in a grid of ROWS by COLUMNS whose top-left brick
+// is at (LEFT, TOP).
+const BRICK_WIDTH: usize = 4;
+const ROWS: usize = 6;
+const COLUMNS: usize = 19;
+const LEFT: usize = (BUFFER_WIDTH - COLUMNS * BRICK_WIDTH) / 2;
+const TOP: usize = 3;
+
+/// The levels, in order. Each digit is a brick needing that many hits; '.' is a gap.
+const LEVELS: [[&str; ROWS]; 3] = [
+    [
+        "...................",
+        "1111111111111111111",
+        "1111111111111111111",
+        "1111111111111111111",
+        "1111111111111111111",
+        "...................",
+    ],
+    [
+        "2222222222222222222",
+        "2.1.1.1.1.1.1.1.1.2",
+        "2111111111111111112",
+        "2.1.1.1.1.1.1.1.1.2",
+        "2111111111111111112",
+        "2222222222222222222",
+    ],
+    [
+        "3.3.3.3.3.3.3.3.3.3",
+        ".2.2.2.2.2.2.2.2.2.",
+        "3.3.3.3.3.3.3.3.3.3",
+        "1111111111111111111",
+        "1111111111111111111",
+        "2222222222222222222",
+    ],
+];
+
+/// The bricks still standing, the level they belong to and the lives left.
+pub struct BreakoutState {
+    // Hits each brick still needs; 0 for no brick.
+    bricks: [[u8; COLUMNS]; ROWS],
+    level: usize,
+    lives: u32,
+    cleared: bool,
+}
+
+impl BreakoutState {
+    pub fn new() -> Self {
+        let mut state = Self { bricks: [[0; COLUMNS]; ROWS], level: 0, lives: START_LIVES, cleared: false };
+        state.load_level(0);
+        state
+    }
+
+    fn load_level(&mut self, level: usize) {
+        self.level = level;
+        for (row, layout) in self.bricks.iter_mut().zip(LEVELS[level]) {
+            for (brick, c) in row.iter_mut().zip(layout.bytes()) {
+                *brick = if c.is_ascii_digit() { c - b'0' } else { 0 };
+            }
+        }
+    }
+
+    /// The current level, starting from 1.
+    pub fn level(&self) -> usize {
+        self.level + 1
+    }
+
+    pub fn lives(&self) -> u32 {
+        self.lives
+    }
+
+    /// Bricks left standing on this level.
+    pub fn bricks_left(&self) -> usize {
+        self.bricks.iter().flatten().filter(|hits| **hits > 0).count()
+    }
+
+    /// Whether the last level has been cleared.
+    pub fn cleared(&self) -> bool {
+        self.cleared
+    }
+
+    /// Hits the brick with block **id**, returning true if that cleared the level. The next
+    /// level, if there is one, is put up straight away.
+    pub fn hit(&mut self, id: usize) -> bool {
+        let brick = &mut self.bricks[id / COLUMNS][id % COLUMNS];
+        *brick = brick.saturating_sub(1);
+        if self.bricks_left() > 0 {
+            return false;
+        }
+        if self.level + 1 < LEVELS.len() {
+            self.load_level(self.level + 1);
+        } else {
+            self.cleared = true;
+        }
+        true
+    }
+
+    /// Fills **blocks** with a block for every brick standing, returning how many there are.
+    pub fn blocks(&self, blocks: &mut [Block]) -> usize {
+        let mut count = 0;
+        for (row, bricks) in self.bricks.iter().enumerate() {
+            for (col, hits) in bricks.iter().enumerate() {
+                if *hits > 0 && count < blocks.len() {
+                    let left = LEFT + col * BRICK_WIDTH;
+                    blocks[count] = Block { id: row * COLUMNS + col, left, right: left + BRICK_WIDTH - 1, row: TOP + row };
+                    count += 1;
+                }
+            }
+        }
+        count
+    }
+}
+
+impl Default for BreakoutState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub struct Breakout;
+
+impl Breakout {
+    /// Bricks, level and lives for the game **game** is playing.
+    pub fn state(game: &Game) -> &BreakoutState {
+        match &game.mode_state {
+            ModeState::Breakout(state) => state,
+            _ => unreachable!("not playing Breakout"),
+        }
+    }
+
+    fn state_mut(game: &mut Game) -> &mut BreakoutState {
+        match &mut game.mode_state {
+            ModeState::Breakout(state) => state,
+            _ => unreachable!("not playing Breakout"),
+        }
+    }
+}
+
+impl GameMode for Breakout {
+    fn name(&self) -> &'static str {
+        "Breakout"
+    }
+
+    fn menu_item(&self) -> MenuItem {
+        MenuItem::new("[B]reakout", Some('b'))
+    }
+
+    /// Bricks take the net color, or the accent color while they need more than one hit.
+    fn palette(&self) -> Palette {
+        Palette {
+            field: Color::Black,
+            lines: Color::LightGray,
+            accent: Color::LightRed,
+            net: Color::Yellow,
+            ball: Color::White,
+            player1: Color::LightCyan,
+            player2: Color::LightCyan,
+            player3: Color::LightCyan,
+            player4: Color::LightCyan,
+            hud: Color::Yellow,
+        }
+    }
+
+    /// Walls on three sides; the bottom is open.
+    fn draw_field(&self, screen: &mut dyn Surface, palette: &Palette) {
+        let wall_color = ColorCode::new(palette.lines, palette.field);
+        for y in 0..BUFFER_HEIGHT {
+            screen.plot('|', 0, y, wall_color);
+            screen.plot('|', BUFFER_WIDTH - 1, y, wall_color);
+        }
+        for x in 0..BUFFER_WIDTH {
+            screen.plot('-', x, 0, wall_color);
+        }
+    }
+
+    fn players(&self) -> usize {
+        1
+    }
+
+    fn paddles(&self) -> [Player; MAX_PLAYERS] {
+        let mut paddles = super::standard_paddles();
+        paddles[0] = Player::horizontal((BUFFER_WIDTH - PADDLE_WIDTH) / 2, BUFFER_HEIGHT - 1, PADDLE_WIDTH);
+        paddles
+    }
+
+    fn serve_speed(&self) -> Fixed {
+        Fixed::from_ratio(1, 2)
+    }
+
+    fn max_ball_speed(&self) -> Fixed {
+        Fixed::ONE
+    }
+
+    fn arena(&self, _game: &Game) -> Arena {
+        let mut arena = Arena::full_screen();
+        arena.left = EndLine::closed(0);
+        arena.right = EndLine::closed(BUFFER_WIDTH - 1);
+        arena.open_floor = true;
+        arena
+    }
+
+    fn new_state(&self) -> ModeState {
+        ModeState::Breakout(BreakoutState::new())
+    }
+
+    /// Launches the ball up and away from the middle of the paddle.
+    fn serve(&self, game: &mut Game, _x_dir: isize) {
+        let paddle = &game.players[0];
+        let x_dir = if game.tick_count % 2 == 0 { 1 } else { -1 };
+        game.ball.reset(paddle.x + paddle.length() / 2, paddle.y - 1, x_dir, -1, self.serve_speed());
+    }
+
+    fn blocks(&self, game: &Game, blocks: &mut [Block]) -> usize {
+        Self::state(game).blocks(blocks)
+    }
+
+    /// Each hit on a brick scores a point. A dropped ball costs a life.
+    fn handle_contact(&self, game: &mut Game, contact: Contact) {
+        match contact {
+            Contact::Block(id) => {
+                game.score1 += 1;
+                let state = Self::state_mut(game);
+                if state.hit(id) && !state.cleared {
+                    game.serve(1);
+                }
+            }
+            Contact::Out(_) => {
+                let state = Self::state_mut(game);
+                state.lives = state.lives.saturating_sub(1);
+                if state.lives > 0 {
+                    game.serve(1);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn outcome(&self, game: &Game) -> Option<Outcome> {
+        let state = Self::state(game);
+        if state.cleared {
+            Some(Outcome::Player1)
+        } else if state.lives == 0 || game.clock.time_up() {
+            Some(Outcome::Player2)
+        } else {
+            None
+        }
+    }
+
+    fn outcome_title(&self, outcome: Outcome) -> &'static str {
+        if outcome == Outcome::Player1 { "YOU WIN!" } else { "GAME OVER" }
+    }
+
+    fn describe_scoring(&self, _game: &Game, line: &mut TextLine) {
+        let _ = write!(line, "Clear {} levels of bricks with {} lives", LEVELS.len(), START_LIVES);
+    }
+
+    fn describe_result(&self, game: &Game, line: &mut TextLine) {
+        let _ = write!(line, "Score {} on level {}", game.score1, Self::state(game).level());
+    }
+
+    fn draw_objects(&self, game: &Game, screen: &mut dyn Surface) {
+        let palette = game.palette();
+        for (row, bricks) in Self::state(game).bricks.iter().enumerate() {
+            for (col, hits) in bricks.iter().enumerate() {
+                let (face, color) = match hits {
+                    0 => continue,
+                    1 => ("[--]", palette.net),
+                    2 => ("[==]", palette.accent),
+                    _ => ("[##]", palette.accent),
+                };
+                screen.plot_str(face, LEFT + col * BRICK_WIDTH, TOP + row, ColorCode::new(color, palette.field));
+            }
+        }
+    }
+
+    /// Score and level on the left of row 1, lives on the right.
+    fn draw_score(&self, game: &Game, screen: &mut dyn Surface) {
+        let palette = game.palette();
+        let color = ColorCode::new(palette.hud, palette.field);
+        let mut line = TextLine::new();
+        let state = Self::state(game);
+        let _ = write!(line, "SCORE {}  LEVEL {}", game.score1, state.level());
+        screen.plot_str(line.as_str(), 2, 1, color);
+        line.clear();
+        let _ = write!(line, "LIVES {}", state.lives);
+        screen.plot_str(line.as_str(), BUFFER_WIDTH - 2 - line.len(), 1, color);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pc_keyboard::KeyCode;
+    use crate::harness::{Simulation, Step};
+    use crate::GameState;
+    use crate::modes::MAX_BLOCKS;
+
+    #[test]
+    fn breakout_levels_follow_the_layouts() {
+        let mut state = BreakoutState::new();
+        let mut blocks = [Block::EMPTY; MAX_BLOCKS];
+        let mut level = 1;
+        while !state.cleared() {
+            assert_eq!(state.level(), level);
+            let count = state.blocks(&mut blocks);
+            assert_eq!(count, state.bricks_left());
+            let mut next_level = false;
+            while !next_level {
+                next_level = state.hit(blocks[0].id);
+                state.blocks(&mut blocks);
+            }
+            level += 1;
+        }
+        assert_eq!(level, 4);
+    }
+
+    #[test]
+    fn breakout_bricks_and_lives() {
+        let mut sim = Simulation::new();
+        sim.run(&[Step::raw(KeyCode::Enter), Step::char('b'), Step::Ticks(1)]);
+        assert_eq!(sim.game().state(), GameState::Playing);
+        assert_eq!(sim.game().mode().name(), "Breakout");
+        assert!(sim.screen_contains("[--][--][--]"));
+        assert!(sim.screen_contains("LIVES 3"));
+        let paddle = sim.game().player1();
+        assert!(paddle.is_horizontal());
+        assert_eq!(paddle.y, BUFFER_HEIGHT - 1);
+        let x = paddle.x;
+        sim.run(&[Step::char('d'), Step::char('d'), Step::char('a'), Step::Ticks(1)]);
+        assert_eq!(sim.game().player1().x, x + 1);
+
+        // A ball heading up into the lowest row of bricks breaks one and comes back down
+        let bricks = Breakout::state(sim.game()).bricks_left();
+        let game = sim.game_mut();
+        game.restart_ticks = 0;
+        game.ball.place(Fixed::from_int(4), Fixed::from_int(9), Fixed::ZERO, -Fixed::ONE);
+        sim.ticks(3);
+        assert_eq!(Breakout::state(sim.game()).bricks_left(), bricks - 1);
+        assert_eq!(sim.game().scores().0, 1);
+        assert!(sim.game().ball().y_velocity() > Fixed::ZERO);
+
+        // Missing the ball costs a life, and the last one ends the game
+        for lives in [2, 1, 0] {
+            let game = sim.game_mut();
+            game.restart_ticks = 0;
+            game.ball.place(Fixed::from_int(2), Fixed::from_int(22), Fixed::ZERO, Fixed::ONE);
+            sim.ticks(3);
+            assert_eq!(Breakout::state(sim.game()).lives(), lives);
+        }
+        assert_eq!(sim.game().state(), GameState::GameOver);
+        assert!(sim.screen_contains("GAME OVER"));
+        assert!(sim.screen_contains("Score 1 on level 1"));
+    }
+}
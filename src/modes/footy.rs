@@ -0,0 +1,174 @@
+// Footy: soccer with paddles.
+//
+// Goals are only scored between the posts at each end of the field; shots that reach an
+// end line anywhere else bounce back into play. A match is played in two halves.
+
+use crate::platform::vga_buffer::{BUFFER_WIDTH, BUFFER_HEIGHT, ColorCode, Color};
+use crate::display::Surface;
+use crate::fixed::Fixed;
+use crate::menu::MenuItem;
+use crate::physics::{Arena, EndLine};
+use crate::theme::Palette;
+use crate::Game;
+use super::GameMode;
+
+// Rows between the goal posts at each end of the field.
+const GOAL_MOUTH_TOP: usize = BUFFER_HEIGHT / 2 - 3;
+const GOAL_MOUTH_BOTTOM: usize = BUFFER_HEIGHT / 2 + 3;
+// Columns of goal net drawn at each end, from the boundary inward over the end line.
+const GOAL_DEPTH: usize = 2;
+
+pub struct Footy;
+
+impl GameMode for Footy {
+    fn name(&self) -> &'static str {
+        "Footy"
+    }
+
+    fn menu_item(&self) -> MenuItem {
+        MenuItem::new("[F]ooty", Some('f'))
+    }
+
+    fn palette(&self) -> Palette {
+        Palette {
+            field: Color::Green,
+            lines: Color::White,
+            accent: Color::White,
+            net: Color::LightGray,
+            ball: Color::White,
+            player1: Color::Blue,
+            player2: Color::Red,
+            player3: Color::Yellow,
+            player4: Color::Pink,
+            hud: Color::Yellow,
+        }
+    }
+
+    fn draw_field(&self, screen: &mut dyn Surface, palette: &Palette) {
+        let field_color = ColorCode::new(palette.lines, palette.field);
+
+        // Draw the outer boundary
+        for y in 0..BUFFER_HEIGHT {
+            screen.plot('|', 0, y, field_color);
+            screen.plot('|', BUFFER_WIDTH - 1, y, field_color);
+        }
+        for x in 0..BUFFER_WIDTH {
+            screen.plot('-', x, 0, field_color);
+            screen.plot('-', x, BUFFER_HEIGHT - 1, field_color);
+        }
+
+        // Draw the goals: posts either side of the mouth, with the net between them
+        let net_color = ColorCode::new(palette.net, palette.field);
+        for y in GOAL_MOUTH_TOP..=GOAL_MOUTH_BOTTOM {
+            for depth in 0..GOAL_DEPTH {
+                let net = if (y + depth) % 2 == 0 { '+' } else { 'x' };
+                screen.plot(net, depth, y, net_color);
+                screen.plot(net, BUFFER_WIDTH - 1 - depth, y, net_color);
+            }
+        }
+        for depth in 0..GOAL_DEPTH {
+            for y in [GOAL_MOUTH_TOP - 1, GOAL_MOUTH_BOTTOM + 1] {
+                screen.plot('=', depth, y, field_color);
+                screen.plot('=', BUFFER_WIDTH - 1 - depth, y, field_color);
+            }
+        }
+
+        // Draw the halfway line
+        let halfway_x = BUFFER_WIDTH / 2;
+        for y in 0..BUFFER_HEIGHT {
+            screen.plot('|', halfway_x, y, field_color);
+        }
+
+        // Draw the penalty boxes
+        let box_width = BUFFER_WIDTH / 6; // Decrease the width
+        let box_height = (3 * BUFFER_HEIGHT) / 4; // Increase the height
+        let top_y = (BUFFER_HEIGHT - box_height) / 2;
+        let bottom_y = top_y + box_height;
+
+        // Left penalty box
+        for y in top_y..=bottom_y {
+            screen.plot('|', box_width, y, field_color);
+        }
+        for x in 0..=box_width {
+            screen.plot('-', x, top_y, field_color);
+            screen.plot('-', x, bottom_y, field_color);
+        }
+
+        // Right penalty box
+        for y in top_y..=bottom_y {
+            screen.plot('|', BUFFER_WIDTH - box_width - 1, y, field_color);
+        }
+        for x in (BUFFER_WIDTH - box_width)..BUFFER_WIDTH {
+            screen.plot('-', x, top_y, field_color);
+            screen.plot('-', x, bottom_y, field_color);
+        }
+
+        // Center circle
+        // TBD
+
+        // Penalty arcs
+        // TBD
+
+    }
+
+    fn periods(&self) -> u32 {
+        2
+    }
+
+    fn period_length(&self) -> Option<u32> {
+        Some(180)
+    }
+
+    fn period_names(&self) -> (char, &'static str) {
+        ('H', "halves")
+    }
+
+    fn max_ball_speed(&self) -> Fixed {
+        Fixed::from_ratio(3, 2)
+    }
+
+    fn arena(&self, _game: &Game) -> Arena {
+        let mut arena = Arena::full_screen();
+        arena.left = EndLine { col: 0, mouth_top: GOAL_MOUTH_TOP, mouth_bottom: GOAL_MOUTH_BOTTOM };
+        arena.right = EndLine { col: BUFFER_WIDTH - 1, mouth_top: GOAL_MOUTH_TOP, mouth_bottom: GOAL_MOUTH_BOTTOM };
+        arena
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pc_keyboard::KeyCode;
+    use crate::harness::{Simulation, Step};
+    use crate::GameState;
+    use crate::rules::MAX_TARGET_SCORE;
+
+    #[test]
+    fn footy_scores_only_between_the_posts() {
+        let mut sim = Simulation::start_match('f', '0');
+        assert!(sim.run_until(100, |game| game.restart_ticks == 0));
+        let speed = Fixed::ONE;
+        sim.game_mut().ball.place(Fixed::from_int(4), Fixed::from_int(2), -speed, Fixed::ZERO);
+        sim.ticks(6);
+        assert_eq!(sim.game().scores(), (0, 0));
+        assert!(sim.game().ball().x_velocity() > Fixed::ZERO);
+
+        let row = Fixed::from_int(BUFFER_HEIGHT as i32 / 2);
+        sim.game_mut().ball.place(Fixed::from_int(BUFFER_WIDTH as i32 - 4), row, speed, Fixed::ZERO);
+        sim.game_mut().players[1].y = 0;
+        assert!(sim.run_until(10, |game| game.scores() == (1, 0)));
+    }
+
+    #[test]
+    fn footy_plays_halves_without_a_time_limit() {
+        let mut sim = Simulation::new();
+        let mut rules = sim.game().rules();
+        rules.target_score = MAX_TARGET_SCORE;
+        sim.game_mut().set_rules(rules);
+        assert_eq!(rules.time_limit, None);
+        sim.run(&[Step::raw(KeyCode::Enter), Step::char('f'), Step::char('0'), Step::Ticks(1)]);
+        assert!(sim.screen_contains("H1 "));
+        assert!(sim.run_until(100_000, |game| game.state() == GameState::Halftime));
+        assert_eq!(sim.game().clock().periods(), 2);
+    }
+}
@@ -0,0 +1,343 @@
+// Four Player.
+//
+// Each player defends one edge of the screen: players 1 and 2 the left and right ends with
+// the usual paddles, players 3 and 4 the top and bottom rows with paddles that run along
+// them. Letting the ball past your paddle costs a life. A player out of lives leaves play
+// and their edge becomes a wall, and the last player standing wins.
+
+use core::fmt::Write;
+use crate::platform::vga_buffer::{BUFFER_WIDTH, BUFFER_HEIGHT, ColorCode, Color};
+use crate::display::{Surface, TextLine};
+use crate::fixed::Fixed;
+use crate::menu::MenuItem;
+use crate::physics::{Arena, Contact, Edge, EndLine, Side};
+use crate::rules::Outcome;
+use crate::theme::Palette;
+use crate::{Game, MAX_PLAYERS};
+use super::{GameMode, ModeState};
+
+pub const START_LIVES: u32 = 3;
+// Each player's lives are shown as e.g. "P1 3", ENTRY_SPACING columns apart along SCORE_ROW.
+const SCORE_ROW: usize = 2;
+const ENTRY_SPACING: usize = 6;
+
+/// Lives left for each player in a game of Four Player, by seat.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FourPlayerState {
+    lives: [u32; MAX_PLAYERS],
+}
+
+impl FourPlayerState {
+    pub fn new() -> Self {
+        Self { lives: [START_LIVES; MAX_PLAYERS] }
+    }
+
+    pub fn lives(&self, seat: usize) -> u32 {
+        self.lives[seat]
+    }
+
+    /// Whether the player in **seat** has lives left.
+    pub fn in_play(&self, seat: usize) -> bool {
+        self.lives[seat] > 0
+    }
+
+    /// How many players have lives left.
+    pub fn remaining(&self) -> usize {
+        self.lives.iter().filter(|&&lives| lives > 0).count()
+    }
+
+    pub fn lose_life(&mut self, seat: usize) {
+        self.lives[seat] = self.lives[seat].saturating_sub(1);
+    }
+
+    /// The seat of the player with the most lives, or None if two or more share the most.
+    pub fn leader(&self) -> Option<usize> {
+        let most = self.lives.iter().copied().max().unwrap_or(0);
+        let mut leaders = (0..MAX_PLAYERS).filter(|&seat| self.lives[seat] == most);
+        let leader = leaders.next();
+        if leaders.next().is_some() { None } else { leader }
+    }
+}
+
+impl Default for FourPlayerState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub struct FourPlayer;
+
+impl FourPlayer {
+    /// Lives left for each player in the match **game** is playing.
+    pub fn state(game: &Game) -> &FourPlayerState {
+        match &game.mode_state {
+            ModeState::FourPlayer(state) => state,
+            _ => unreachable!("not playing Four Player"),
+        }
+    }
+
+    fn state_mut(game: &mut Game) -> &mut FourPlayerState {
+        match &mut game.mode_state {
+            ModeState::FourPlayer(state) => state,
+            _ => unreachable!("not playing Four Player"),
+        }
+    }
+}
+
+impl GameMode for FourPlayer {
+    fn name(&self) -> &'static str {
+        "Four Player"
+    }
+
+    fn menu_item(&self) -> MenuItem {
+        MenuItem::new("[4] Four Player", Some('4'))
+    }
+
+    fn palette(&self) -> Palette {
+        Palette {
+            field: Color::Black,
+            lines: Color::DarkGray,
+            accent: Color::White,
+            net: Color::DarkGray,
+            ball: Color::White,
+            player1: Color::LightCyan,
+            player2: Color::LightRed,
+            player3: Color::LightGreen,
+            player4: Color::Yellow,
+            hud: Color::White,
+        }
+    }
+
+    /// Marks the corners and the center spot.
+    fn draw_field(&self, screen: &mut dyn Surface, palette: &Palette) {
+        let color = ColorCode::new(palette.lines, palette.field);
+        for (x, y) in [(0, 0), (BUFFER_WIDTH - 1, 0), (0, BUFFER_HEIGHT - 1), (BUFFER_WIDTH - 1, BUFFER_HEIGHT - 1)] {
+            screen.plot('+', x, y, color);
+        }
+        screen.plot('+', BUFFER_WIDTH / 2, BUFFER_HEIGHT / 2, color);
+    }
+
+    fn players(&self) -> usize {
+        MAX_PLAYERS
+    }
+
+    fn seat_active(&self, game: &Game, seat: usize) -> bool {
+        Self::state(game).in_play(seat)
+    }
+
+    fn max_ball_speed(&self) -> Fixed {
+        Fixed::from_ratio(5, 4)
+    }
+
+    /// Every edge is open while its player is in play, and a wall once they are out.
+    fn arena(&self, game: &Game) -> Arena {
+        let state = Self::state(game);
+        let mut arena = Arena::full_screen();
+        if !state.in_play(0) {
+            arena.left = EndLine::closed(0);
+        }
+        if !state.in_play(1) {
+            arena.right = EndLine::closed(BUFFER_WIDTH - 1);
+        }
+        arena.open_ceiling = state.in_play(2);
+        arena.open_floor = state.in_play(3);
+        arena
+    }
+
+    fn new_state(&self) -> ModeState {
+        ModeState::FourPlayer(FourPlayerState::new())
+    }
+
+    /// Serves from the center spot toward a randomly chosen corner.
+    fn serve(&self, game: &mut Game, _x_dir: isize) {
+        let x_dir = if game.tick_count % 2 == 0 { 1 } else { -1 };
+        let y_dir = if game.tick_count / 2 % 2 == 0 { 1 } else { -1 };
+        game.ball.reset(BUFFER_WIDTH / 2, BUFFER_HEIGHT / 2, x_dir, y_dir, self.serve_speed());
+    }
+
+    /// The ball leaving by an edge costs the player defending it a life.
+    fn handle_contact(&self, game: &mut Game, contact: Contact) {
+        let seat = match contact {
+            Contact::Goal(Side::Left) => 0,
+            Contact::Goal(Side::Right) => 1,
+            Contact::Out(Edge::Top) => 2,
+            Contact::Out(Edge::Bottom) => 3,
+            _ => return,
+        };
+        Self::state_mut(game).lose_life(seat);
+        if self.outcome(game).is_none() {
+            game.serve(1);
+        }
+    }
+
+    /// The last player standing wins. When time runs out the player with the most lives
+    /// wins; a tie for the most is a draw, or plays on under the golden goal rule.
+    fn outcome(&self, game: &Game) -> Option<Outcome> {
+        let state = Self::state(game);
+        if state.remaining() > 1 && !game.clock.time_up() {
+            return None;
+        }
+        match state.leader() {
+            Some(seat) => Some(Outcome::win_for(seat)),
+            None if game.rules.golden_goal => None,
+            None => Some(Outcome::Draw),
+        }
+    }
+
+    fn describe_scoring(&self, _game: &Game, line: &mut TextLine) {
+        let _ = write!(line, "Defend your wall: {} lives each, last player standing wins", START_LIVES);
+    }
+
+    fn describe_result(&self, game: &Game, line: &mut TextLine) {
+        let _ = line.write_str("Lives left:");
+        for seat in 0..MAX_PLAYERS {
+            let _ = write!(line, "  P{} {}", seat + 1, Self::state(game).lives(seat));
+        }
+    }
+
+    /// Draws a wall along the edge of each player who is out.
+    fn draw_objects(&self, game: &Game, screen: &mut dyn Surface) {
+        let palette = game.palette();
+        let color = ColorCode::new(palette.lines, palette.field);
+        let state = Self::state(game);
+        for seat in (0..MAX_PLAYERS).filter(|&seat| !state.in_play(seat)) {
+            match seat {
+                0 | 1 => {
+                    let x = if seat == 0 { 0 } else { BUFFER_WIDTH - 1 };
+                    for y in 0..BUFFER_HEIGHT {
+                        screen.plot('|', x, y, color);
+                    }
+                }
+                _ => {
+                    let y = if seat == 2 { 0 } else { BUFFER_HEIGHT - 1 };
+                    for x in 0..BUFFER_WIDTH {
+                        screen.plot('-', x, y, color);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Each player's lives, in their own color, across SCORE_ROW.
+    fn draw_score(&self, game: &Game, screen: &mut dyn Surface) {
+        let palette = game.palette();
+        let left = (BUFFER_WIDTH - ENTRY_SPACING * MAX_PLAYERS) / 2;
+        let mut line = TextLine::new();
+        for seat in 0..MAX_PLAYERS {
+            line.clear();
+            let _ = write!(line, "P{} {}", seat + 1, Self::state(game).lives(seat));
+            let color = ColorCode::new(palette.player(seat), palette.field);
+            screen.plot_str(line.as_str(), left + seat * ENTRY_SPACING, SCORE_ROW, color);
+        }
+    }
+
+    fn clock_row(&self) -> usize {
+        BUFFER_HEIGHT - 3
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pc_keyboard::KeyCode;
+    use crate::harness::{Simulation, Step};
+    use crate::GameState;
+
+    #[test]
+    fn lives_run_out_one_seat_at_a_time() {
+        let mut state = FourPlayerState::new();
+        assert_eq!(state.remaining(), MAX_PLAYERS);
+        assert_eq!(state.leader(), None);
+        state.lose_life(2);
+        assert_eq!(state.lives(2), START_LIVES - 1);
+        for _ in 0..START_LIVES {
+            state.lose_life(2);
+        }
+        assert_eq!(state.lives(2), 0);
+        assert!(!state.in_play(2));
+        assert_eq!(state.remaining(), MAX_PLAYERS - 1);
+    }
+
+    #[test]
+    fn the_most_lives_leads_unless_shared() {
+        let mut state = FourPlayerState::new();
+        state.lose_life(0);
+        state.lose_life(1);
+        assert_eq!(state.leader(), None);
+        state.lose_life(3);
+        assert_eq!(state.leader(), Some(2));
+    }
+
+    #[test]
+    fn last_player_standing_wins() {
+        let mut game = Game::new();
+        game.set_mode(&FourPlayer);
+        game.start_match();
+        for seat in [0, 2, 3] {
+            for _ in 0..START_LIVES {
+                FourPlayer::state_mut(&mut game).lose_life(seat);
+            }
+            assert!(!FourPlayer.seat_active(&game, seat));
+        }
+        assert_eq!(FourPlayer.outcome(&game), Some(Outcome::Player2));
+    }
+
+    #[test]
+    fn four_player_seats_lives_and_elimination() {
+        // Sends the ball from (**x**, **y**) at the given velocity for a few ticks.
+        fn send(sim: &mut Simulation, x: i32, y: i32, x_velocity: Fixed, y_velocity: Fixed) {
+            let game = sim.game_mut();
+            game.restart_ticks = 0;
+            game.ball.place(Fixed::from_int(x), Fixed::from_int(y), x_velocity, y_velocity);
+            sim.ticks(3);
+        }
+
+        let mut sim = Simulation::start_match('4', '1');
+        sim.ticks(1);
+        assert_eq!(sim.game().state(), GameState::SeatSelect);
+        assert!(sim.screen_contains("Choose Players:"));
+        assert!(sim.screen_contains("Human"));
+        assert!(sim.screen_contains("CPU"));
+
+        // Against the CPU it takes every seat but player 1's; hand player 3 back to a human
+        sim.run(&[Step::raw(KeyCode::ArrowUp), Step::raw(KeyCode::ArrowUp), Step::raw(KeyCode::ArrowRight)]);
+        sim.run(&[Step::char('s'), Step::Ticks(1)]);
+        assert_eq!(sim.game().state(), GameState::Playing);
+        let seats: Vec<bool> = (0..4).map(|seat| sim.game().is_cpu(seat)).collect();
+        assert_eq!(seats, [false, true, false, true]);
+        assert!(sim.screen_contains("P1 3  P2 3  P3 3  P4 3"));
+
+        let top = sim.game().player(2);
+        assert!(top.is_horizontal());
+        assert_eq!(top.y, 0);
+        assert_eq!(sim.game().player(3).y, BUFFER_HEIGHT - 1);
+        let x = top.x;
+        sim.run(&[Step::char('l'), Step::char('l'), Step::char('j'), Step::Ticks(1)]);
+        assert_eq!(sim.game().player(2).x, x + 1);
+
+        // Player 3 returns a ball that reaches their paddle, and loses a life to one that doesn't
+        send(&mut sim, x as i32 + 5, 2, Fixed::ZERO, -Fixed::ONE);
+        assert!(sim.game().ball().y_velocity() > Fixed::ZERO);
+        assert_eq!(FourPlayer::state(sim.game()).lives(2), 3);
+        for lives in [2, 1, 0] {
+            send(&mut sim, 5, 2, Fixed::ZERO, -Fixed::ONE);
+            assert_eq!(FourPlayer::state(sim.game()).lives(2), lives);
+        }
+
+        // Once out, their paddle leaves play and the top edge is a wall
+        send(&mut sim, 5, 2, Fixed::ZERO, -Fixed::ONE);
+        assert!(sim.game().ball().y_velocity() > Fixed::ZERO);
+        assert_eq!(sim.screen().cell(x + 5, 0).ch, '-');
+
+        for _ in 0..3 {
+            send(&mut sim, 1, 20, -Fixed::ONE, Fixed::ZERO);
+            send(&mut sim, 5, 22, Fixed::ZERO, Fixed::ONE);
+        }
+        assert_eq!(FourPlayer::state(sim.game()).lives(0), 0);
+        assert_eq!(FourPlayer::state(sim.game()).lives(3), 0);
+        assert_eq!(sim.game().state(), GameState::GameOver);
+        assert_eq!(sim.game().outcome(), Some(Outcome::Player2));
+        assert!(sim.screen_contains("Player 2 WINS!"));
+        assert!(sim.screen_contains("Lives left:  P1 0  P2 3  P3 0  P4 0"));
+    }
+}
@@ -0,0 +1,299 @@
+// Hockey.
+//
+// The nets stand in from the end boards with the goal line along their front, so the puck
+// can travel behind them; it only scores through the front. Play restarts with a face-off,
+// and icing is called on a puck shot the length of the rink from behind the shooter's own
+// blue line. A match is played in three periods.
+
+use crate::platform::vga_buffer::{BUFFER_WIDTH, BUFFER_HEIGHT, ColorCode, Color};
+use crate::display::Surface;
+use crate::fixed::Fixed;
+use crate::menu::MenuItem;
+use crate::physics::{Arena, EndLine, Net, Side};
+use crate::{Game, Player, MAX_PLAYERS, PADDLE_HEIGHT, TICKS_PER_SECOND};
+use crate::theme::Palette;
+use super::{GameMode, ModeState};
+
+// Nets stand this many columns in from the end boards. The right-hand rink is a mirror image.
+const GOAL_LINE: usize = 7;
+const NET_DEPTH: usize = 2;
+const NET_TOP: usize = BUFFER_HEIGHT / 2 - 3;
+const NET_BOTTOM: usize = BUFFER_HEIGHT / 2 + 3;
+// How long the puck sits on the face-off spot before it is dropped.
+const FACEOFF_TICKS: usize = TICKS_PER_SECOND;
+
+/// What Hockey keeps track of for icing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct HockeyState {
+    /// Whether the face-off under way was called for icing.
+    pub icing: bool,
+    /// The player who last touched the puck, and the column they played it from.
+    pub last_touch: Option<(usize, usize)>,
+}
+
+pub struct Hockey;
+
+impl Hockey {
+    /// Icing and the last touch in the match **game** is playing.
+    pub fn state(game: &Game) -> &HockeyState {
+        match &game.mode_state {
+            ModeState::Hockey(state) => state,
+            _ => unreachable!("not playing Hockey"),
+        }
+    }
+
+    fn state_mut(game: &mut Game) -> &mut HockeyState {
+        match &mut game.mode_state {
+            ModeState::Hockey(state) => state,
+            _ => unreachable!("not playing Hockey"),
+        }
+    }
+
+    fn net(side: Side) -> Net {
+        let (front, back) = match side {
+            Side::Left => (GOAL_LINE, GOAL_LINE - NET_DEPTH),
+            Side::Right => (BUFFER_WIDTH - 1 - GOAL_LINE, BUFFER_WIDTH - 1 - GOAL_LINE + NET_DEPTH),
+        };
+        Net { side, front, back, top: NET_TOP, bottom: NET_BOTTOM }
+    }
+
+    /// Holds the puck on the center row of column **col** for FACEOFF_TICKS, then drops it
+    /// toward a randomly chosen end.
+    fn face_off(&self, game: &mut Game, col: usize) {
+        let x_dir = if game.tick_count % 2 == 0 { 1 } else { -1 };
+        let y_dir = if game.tick_count / 2 % 2 == 0 { 1 } else { -1 };
+        game.ball.reset(col, BUFFER_HEIGHT / 2, x_dir, y_dir, self.serve_speed());
+        game.restart_ticks = FACEOFF_TICKS;
+        Self::state_mut(game).last_touch = None;
+    }
+}
+
+impl GameMode for Hockey {
+    fn name(&self) -> &'static str {
+        "Hockey"
+    }
+
+    fn menu_item(&self) -> MenuItem {
+        MenuItem::new("[H]ockey", Some('h'))
+    }
+
+    fn palette(&self) -> Palette {
+        Palette {
+            field: Color::White,
+            lines: Color::Blue,
+            accent: Color::Red,
+            net: Color::DarkGray,
+            ball: Color::Black,
+            player1: Color::Blue,
+            player2: Color::Red,
+            player3: Color::Green,
+            player4: Color::Magenta,
+            hud: Color::Yellow,
+        }
+    }
+
+    fn draw_field(&self, screen: &mut dyn Surface, palette: &Palette) {
+        let court_color = ColorCode::new(palette.lines, palette.field);
+        let red_lines = ColorCode::new(palette.accent, palette.field);
+    
+        // Draw the outer boundary
+        for y in 0..BUFFER_HEIGHT {
+            screen.plot('|', 0, y, court_color);
+            screen.plot('|', BUFFER_WIDTH - 1, y, court_color);
+        }
+        for x in 0..BUFFER_WIDTH {
+            screen.plot('-', x, 0, court_color);
+            screen.plot('-', x, BUFFER_HEIGHT - 1, court_color);
+        }
+    
+        // Draw the vertical lines
+        let vertical_x1 = BUFFER_WIDTH / 4;
+        let vertical_x2 = BUFFER_WIDTH / 2;
+        let vertical_x3 = BUFFER_WIDTH * 3 / 4;
+    
+        for y in 0..BUFFER_HEIGHT {
+            screen.plot('|', vertical_x1, y, court_color);
+            screen.plot('|', vertical_x3, y, court_color);
+            screen.plot('|', vertical_x2, y, red_lines);
+        }
+
+        // Draw the goal lines and the nets standing on them
+        let net_color = ColorCode::new(palette.net, palette.field);
+        for side in [Side::Left, Side::Right] {
+            let net = Self::net(side);
+            for y in 1..BUFFER_HEIGHT - 1 {
+                screen.plot('|', net.front, y, red_lines);
+            }
+            let (left, right) = (net.front.min(net.back), net.front.max(net.back));
+            for y in net.top..=net.bottom {
+                for x in left..=right {
+                    let c = if y == net.top || y == net.bottom {
+                        '='
+                    } else if x == net.front {
+                        ' '
+                    } else {
+                        '+'
+                    };
+                    screen.plot(c, x, y, net_color);
+                }
+            }
+        }
+    }
+
+    fn periods(&self) -> u32 {
+        3
+    }
+
+    fn period_length(&self) -> Option<u32> {
+        Some(120)
+    }
+
+    /// Goalies stand in front of their nets.
+    fn paddles(&self) -> [Player; MAX_PLAYERS] {
+        let y = BUFFER_HEIGHT / 2 - PADDLE_HEIGHT / 2;
+        let mut paddles = super::standard_paddles();
+        paddles[0] = Player::new(GOAL_LINE + 3, y);
+        paddles[1] = Player::new(BUFFER_WIDTH - 4 - GOAL_LINE, y);
+        paddles
+    }
+
+    fn max_ball_speed(&self) -> Fixed {
+        Fixed::from_int(2)
+    }
+
+    fn arena(&self, _game: &Game) -> Arena {
+        let mut arena = Arena::full_screen();
+        arena.left = EndLine::closed(0);
+        arena.right = EndLine::closed(BUFFER_WIDTH - 1);
+        arena.nets = [Some(Self::net(Side::Left)), Some(Self::net(Side::Right))];
+        arena
+    }
+
+    fn new_state(&self) -> ModeState {
+        ModeState::Hockey(HockeyState::default())
+    }
+
+    /// Every restart is a center face-off.
+    fn serve(&self, game: &mut Game, _x_dir: isize) {
+        Self::state_mut(game).icing = false;
+        self.face_off(game, BUFFER_WIDTH / 2);
+    }
+
+    fn paddle_hit(&self, game: &mut Game, seat: usize) {
+        Self::state_mut(game).last_touch = Some((seat, game.ball.col()));
+    }
+
+    /// Calls icing when the puck, last played from behind the shooter's own blue line, has
+    /// crossed the red line, the far blue line and the far goal line without being touched.
+    /// Play restarts with a face-off in the offending player's end.
+    fn after_step(&self, game: &mut Game) {
+        if let Some((player, from)) = Self::state(game).last_touch {
+            let col = game.ball.col();
+            let defends_left = game.defender(Side::Left) == player;
+            let iced = if defends_left {
+                from < BUFFER_WIDTH / 4 && col > BUFFER_WIDTH - 1 - GOAL_LINE
+            } else {
+                from > BUFFER_WIDTH * 3 / 4 && col < GOAL_LINE
+            };
+            if iced {
+                let spot = BUFFER_WIDTH / 4 - 4;
+                Self::state_mut(game).icing = true;
+                self.face_off(game, if defends_left { spot } else { BUFFER_WIDTH - 1 - spot });
+            }
+        }
+    }
+
+    fn draw_restart(&self, game: &Game, screen: &mut dyn Surface) {
+        let message = if Self::state(game).icing { "ICING - FACE-OFF" } else { "FACE-OFF" };
+        let palette = game.palette();
+        screen.plot_centered(message, BUFFER_HEIGHT - 2, ColorCode::new(palette.accent, palette.field));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::harness::Simulation;
+
+    // A match of Hockey with player 1's last touch from column **from** and the puck moving
+    // right from column **col**.
+    fn shot(from: usize, col: i32) -> Game {
+        let mut game = Game::new();
+        game.set_mode(&Hockey);
+        game.start_match();
+        Hockey::state_mut(&mut game).last_touch = Some((0, from));
+        game.ball.place(Fixed::from_int(col), Fixed::from_int(5), Fixed::ONE, Fixed::ZERO);
+        game
+    }
+
+    #[test]
+    fn icing_needs_the_whole_rink() {
+        let mut game = shot(10, BUFFER_WIDTH as i32 - GOAL_LINE as i32);
+        Hockey.after_step(&mut game);
+        assert!(Hockey::state(&game).icing);
+        assert_eq!(game.ball.col(), BUFFER_WIDTH / 4 - 4);
+        assert_eq!(Hockey::state(&game).last_touch, None);
+
+        // Short of the far goal line
+        let mut game = shot(10, BUFFER_WIDTH as i32 - 1 - GOAL_LINE as i32);
+        Hockey.after_step(&mut game);
+        assert!(!Hockey::state(&game).icing);
+    }
+
+    #[test]
+    fn no_icing_from_in_front_of_the_blue_line() {
+        let mut game = shot(BUFFER_WIDTH / 4, BUFFER_WIDTH as i32 - GOAL_LINE as i32);
+        Hockey.after_step(&mut game);
+        assert!(!Hockey::state(&game).icing);
+    }
+
+    #[test]
+    fn a_paddle_hit_is_the_last_touch() {
+        let mut game = shot(10, 40);
+        Hockey.paddle_hit(&mut game, 1);
+        assert_eq!(Hockey::state(&game).last_touch, Some((1, 40)));
+        Hockey.serve(&mut game, 1);
+        assert_eq!(Hockey::state(&game).last_touch, None);
+    }
+
+    #[test]
+    fn a_puck_behind_the_goalie_does_not_score_against_them() {
+        let mut game = Game::new();
+        game.set_mode(&Hockey);
+        game.start_match();
+        let (goalie, row) = (game.players[0].x, (game.players[0].y + PADDLE_HEIGHT / 2) as i32);
+        game.ball.place(Fixed::from_int(goalie as i32 - 2), Fixed::from_int(row), Fixed::ONE, Fixed::ZERO);
+        for _ in 0..4 {
+            let contact = game.step_ball();
+            game.handle_collisions(contact);
+        }
+        assert_eq!((game.score1, game.score2), (0, 0));
+        assert!(game.ball.col() > goalie);
+        assert!(game.ball.x_velocity() > Fixed::ZERO);
+    }
+
+    #[test]
+    fn hockey_puck_rebounds_behind_the_net() {
+        let mut sim = Simulation::start_match('h', '0');
+        assert!(sim.run_until(100, |game| game.restart_ticks == 0));
+        let row = Fixed::from_int(BUFFER_HEIGHT as i32 / 2);
+        sim.game_mut().ball.place(Fixed::from_int(2), row, Fixed::ONE, Fixed::ZERO);
+        sim.ticks(4);
+        assert_eq!(sim.game().scores(), (0, 0));
+        assert!(sim.game().ball().x_velocity() < Fixed::ZERO);
+    }
+
+    #[test]
+    fn hockey_icing_brings_a_face_off() {
+        let mut sim = Simulation::start_match('h', '0');
+        assert!(sim.run_until(100, |game| game.restart_ticks == 0));
+        let game = sim.game_mut();
+        game.ball.place(Fixed::from_int(15), Fixed::from_int(2), Fixed::from_int(2), Fixed::ZERO);
+        game.mode_state = ModeState::Hockey(HockeyState { icing: false, last_touch: Some((0, 15)) });
+        assert!(sim.run_until(60, |game| Hockey::state(game).icing));
+        assert_eq!(sim.game().ball().col(), BUFFER_WIDTH / 4 - 4);
+        sim.ticks(1);
+        assert!(sim.screen_contains("ICING - FACE-OFF"));
+        assert_eq!(sim.game().scores(), (0, 0));
+    }
+}
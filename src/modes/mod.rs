@@ -0,0 +1,236 @@
+// Sports.
+//
+// Each sport is a GameMode: it draws its own field, has its own colors, decides how points
+// are scored and how play restarts, and says how it is listed on the game mode menu. Game
+// runs the parts every sport shares (paddles, the ball, the clock, the menus) and asks the
+// current mode about everything else. A mode itself is a constant; anything a sport needs to
+// remember during a match, such as the tennis score, goes in the ModeState it creates when
+// the match starts, which Game holds for it and the mode's hooks reach through the Game
+// they are handed.
+//
+// Adding a sport means adding a module here, listing it in MODES and, if it keeps anything
+// during a match, giving its state a ModeState variant. The game mode menu is built from
+// MODES.
+//
+// Players sit in numbered seats: player 1 in seat 0 up to player 4 in seat 3. A sport
+// decides which seats are in play; Game only moves, draws and bounces the ball off the
+// paddles in those seats.
+
+pub mod breakout;
+pub mod footy;
+pub mod four_player;
+pub mod hockey;
+pub mod tennis;
+
+use crate::platform::vga_buffer::{BUFFER_WIDTH, BUFFER_HEIGHT, ColorCode};
+use crate::display::{Surface, TextLine};
+use crate::fixed::Fixed;
+use crate::menu::{MenuItem, MAX_MENU_ITEMS};
+use crate::physics::{Arena, Block, Contact};
+use crate::rules::Outcome;
+use crate::theme::Palette;
+use crate::{Game, Player, MAX_PLAYERS, PADDLE_HEIGHT};
+
+/// Every sport on the game mode menu, in menu order.
+pub static MODES: [&dyn GameMode; 5] =
+    [&footy::Footy, &hockey::Hockey, &tennis::Tennis, &breakout::Breakout, &four_player::FourPlayer];
+
+// Every sport must fit on the game mode menu.
+const _: () = assert!(MODES.len() <= MAX_MENU_ITEMS);
+
+/// What the current sport remembers during a match. Each mode creates its own with
+/// **GameMode::new_state** and only ever looks at its own variant.
+pub enum ModeState {
+    /// For sports with nothing to remember.
+    None,
+    Hockey(hockey::HockeyState),
+    Tennis(tennis::TennisScore),
+    Breakout(breakout::BreakoutState),
+    FourPlayer(four_player::FourPlayerState),
+}
+
+/// The most blocks a mode can put in the ball's way.
+pub const MAX_BLOCKS: usize = 128;
+
+/// Width of the paddles that run along the top and bottom rows for players 3 and 4.
+pub const WIDE_PADDLE: usize = 12;
+
+/// A paddle for every seat: players 1 and 2 at the left and right ends, halfway up, and
+/// players 3 and 4 in the middle of the top and bottom rows.
+pub fn standard_paddles() -> [Player; MAX_PLAYERS] {
+    let y = BUFFER_HEIGHT / 2 - PADDLE_HEIGHT / 2;
+    let x = (BUFFER_WIDTH - WIDE_PADDLE) / 2;
+    [
+        Player::new(2, y),
+        Player::new(BUFFER_WIDTH - 3, y),
+        Player::horizontal(x, 0, WIDE_PADDLE),
+        Player::horizontal(x, BUFFER_HEIGHT - 1, WIDE_PADDLE),
+    ]
+}
+
+pub trait GameMode: Sync {
+    /// The sport's name, e.g. "Footy".
+    fn name(&self) -> &'static str;
+
+    /// How the sport is listed on the game mode menu.
+    fn menu_item(&self) -> MenuItem;
+
+    /// The sport's own colors, which the classic theme plays it in.
+    fn palette(&self) -> Palette;
+
+    /// Draws the field markings in **palette** over a screen already cleared to the field color.
+    fn draw_field(&self, screen: &mut dyn Surface, palette: &Palette);
+
+    /// How many halves or periods a timed match is split into.
+    fn periods(&self) -> u32 {
+        1
+    }
+
+    /// How long each half or period lasts, in seconds, when the rules set no time limit.
+    /// None plays an untimed match.
+    fn period_length(&self) -> Option<u32> {
+        None
+    }
+
+    /// The letter shown before the period number on the clock, and the plural name of a
+    /// period, e.g. ('H', "halves").
+    fn period_names(&self) -> (char, &'static str) {
+        ('P', "periods")
+    }
+
+    /// How many players take part: 2, or 1 for a sport played alone, which skips the
+    /// difficulty menu and leaves player 2's paddle out of play. A sport for more than two
+    /// asks which seats the CPU plays before the match starts.
+    fn players(&self) -> usize {
+        2
+    }
+
+    /// Whether the player in **seat** is in play right now, so their paddle is drawn,
+    /// moved and hit. By default the first **players()** seats are.
+    fn seat_active(&self, _game: &Game, seat: usize) -> bool {
+        seat < self.players()
+    }
+
+    /// The paddle for each seat at the start of a match.
+    fn paddles(&self) -> [Player; MAX_PLAYERS] {
+        standard_paddles()
+    }
+
+    /// Horizontal ball speed, in cells per tick, at the start of each rally.
+    fn serve_speed(&self) -> Fixed {
+        Fixed::from_ratio(3, 4)
+    }
+
+    /// The fastest horizontal ball speed a rally can build up to.
+    fn max_ball_speed(&self) -> Fixed;
+
+    /// The walls, end lines and nets the ball moves between.
+    fn arena(&self, _game: &Game) -> Arena {
+        Arena::full_screen()
+    }
+
+    /// A fresh state for a new match, created when the sport is chosen and again before the
+    /// first serve of each match.
+    fn new_state(&self) -> ModeState {
+        ModeState::None
+    }
+
+    /// Fills **blocks** with the bricks the ball can hit, returning how many there are.
+    fn blocks(&self, _game: &Game, _blocks: &mut [Block]) -> usize {
+        0
+    }
+
+    /// Puts the ball back in play. Game has already set a short delay before it moves;
+    /// **x_dir** is the direction favoured by whoever conceded the last point. By default
+    /// the ball restarts from the center spot.
+    fn serve(&self, game: &mut Game, x_dir: isize) {
+        game.ball.reset(BUFFER_WIDTH / 2, BUFFER_HEIGHT / 2, x_dir, 1, self.serve_speed());
+    }
+
+    /// Called after **player** (0 or 1) wins a point and Game has added it to their score.
+    fn point_won(&self, _game: &mut Game, _player: usize) {}
+
+    /// Called when the paddle in **seat** hits the ball, once Game has sent it back.
+    fn paddle_hit(&self, _game: &mut Game, _seat: usize) {}
+
+    /// Called with each contact once Game has dealt with any paddle hit. By default a goal
+    /// scores for whoever defends the other end, and anything else is ignored.
+    fn handle_contact(&self, game: &mut Game, contact: Contact) {
+        if let Contact::Goal(side) = contact {
+            game.score_goal(side);
+        }
+    }
+
+    /// Returns how the match ends, or None if play continues.
+    fn outcome(&self, game: &Game) -> Option<Outcome> {
+        game.rules.outcome(game.score1, game.score2, game.clock.time_up())
+    }
+
+    /// The title of the game over screen.
+    fn outcome_title(&self, outcome: Outcome) -> &'static str {
+        match outcome {
+            Outcome::Player1 => "Player 1 WINS!",
+            Outcome::Player2 => "Player 2 WINS!",
+            Outcome::Player3 => "Player 3 WINS!",
+            Outcome::Player4 => "Player 4 WINS!",
+            Outcome::Draw => "DRAW!",
+        }
+    }
+
+    /// Writes a one-line summary of how a match is won.
+    fn describe_scoring(&self, game: &Game, line: &mut TextLine) {
+        game.rules.describe_scoring(line);
+    }
+
+    /// Writes a line for the game over screen, if the sport has more to say than the score.
+    fn describe_result(&self, _game: &Game, _line: &mut TextLine) {}
+
+    /// Draws anything on the field that changes during play, under the paddles and ball.
+    fn draw_objects(&self, _game: &Game, _screen: &mut dyn Surface) {}
+
+    /// Draws the score over the field.
+    fn draw_score(&self, game: &Game, screen: &mut dyn Surface) {
+        let palette = game.palette();
+        screen.plot_num(game.score1 as isize, 30, 1, ColorCode::new(palette.player1, palette.field));
+        screen.plot_num(game.score2 as isize, 50, 1, ColorCode::new(palette.player2, palette.field));
+    }
+
+    /// The row the match clock is shown on.
+    fn clock_row(&self) -> usize {
+        1
+    }
+
+    /// Called every tick of play once the ball has moved and any point has been scored.
+    fn after_step(&self, _game: &mut Game) {}
+
+    /// Draws anything shown while play waits to restart after a point.
+    fn draw_restart(&self, _game: &Game, _screen: &mut dyn Surface) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pc_keyboard::KeyCode;
+    use crate::harness::{Simulation, Step};
+    use crate::GameState;
+
+    #[test]
+    fn every_registered_mode_is_on_the_menu() {
+        for (i, mode) in MODES.iter().enumerate() {
+            let mut sim = Simulation::new();
+            sim.run(&[Step::raw(KeyCode::Enter), Step::Ticks(1)]);
+            assert!(sim.screen_contains(mode.menu_item().label));
+            for _ in 0..i {
+                sim.run(&[Step::raw(KeyCode::ArrowDown)]);
+            }
+            sim.run(&[Step::raw(KeyCode::Enter), Step::char('0')]);
+            if mode.players() > 2 {
+                sim.run(&[Step::char('s')]);
+            }
+            sim.run(&[Step::Ticks(2)]);
+            assert_eq!(sim.game().state(), GameState::Playing);
+            assert_eq!(sim.game().mode().name(), mode.name());
+            assert_eq!(sim.screen().cell(1, 1).color.background(), mode.palette().field);
+        }
+    }
+}
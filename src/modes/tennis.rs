@@ -0,0 +1,374 @@
+// Tennis.
+//
+// The ball is served from the server's paddle rather than the center of the court.
+// Every rally point still counts toward Game::score1 and Game::score2; TennisScore groups
+// those points into games (15/30/40, deuce and advantage), games into sets, and settles a
+// set at 6-6 with a tie-break. It also tracks who serves: the server alternates each game,
+// and during a tie-break after the first point and then every two points.
+
+use core::fmt::Write;
+use crate::platform::vga_buffer::{BUFFER_WIDTH, BUFFER_HEIGHT, ColorCode, Color};
+use crate::display::{Surface, TextLine};
+use crate::fixed::Fixed;
+use crate::menu::MenuItem;
+use crate::rules::{MatchRules, Outcome};
+use crate::{Game, PADDLE_HEIGHT};
+use crate::theme::Palette;
+use super::{GameMode, ModeState};
+
+pub const GAMES_PER_SET: u32 = 6;
+pub const SETS_TO_WIN: u32 = 2;
+pub const MAX_SETS: usize = (2 * SETS_TO_WIN - 1) as usize;
+const TIEBREAK_POINTS: u32 = 7;
+const POINT_NAMES: [&str; 4] = ["0", "15", "30", "40"];
+
+pub struct TennisScore {
+    points: [u32; 2],
+    games: [u32; 2],
+    sets: [u32; 2],
+    set_scores: [(u32, u32); MAX_SETS],
+    sets_played: usize,
+    server: usize,
+    tiebreak: bool,
+    // Who served the first point of the current tie-break.
+    tiebreak_server: usize,
+    winner: Option<usize>,
+}
+
+impl TennisScore {
+    pub fn new() -> Self {
+        Self {
+            points: [0; 2],
+            games: [0; 2],
+            sets: [0; 2],
+            set_scores: [(0, 0); MAX_SETS],
+            sets_played: 0,
+            server: 0,
+            tiebreak: false,
+            tiebreak_server: 0,
+            winner: None,
+        }
+    }
+
+    /// Awards a point to **player** (0 for player 1, 1 for player 2).
+    pub fn point_won(&mut self, player: usize) {
+        if self.winner.is_some() {
+            return;
+        }
+        let other = 1 - player;
+        self.points[player] += 1;
+        let needed = if self.tiebreak { TIEBREAK_POINTS } else { 4 };
+        if self.points[player] >= needed && self.points[player] - self.points[other] >= 2 {
+            self.game_won(player);
+        }
+    }
+
+    fn game_won(&mut self, player: usize) {
+        let other = 1 - player;
+        self.games[player] += 1;
+        self.points = [0; 2];
+        if self.tiebreak {
+            // The player who received first in the tie-break serves the next set.
+            self.tiebreak = false;
+            self.server = 1 - self.tiebreak_server;
+            self.set_won(player);
+            return;
+        }
+        self.server = 1 - self.server;
+        if self.games[player] >= GAMES_PER_SET && self.games[player] - self.games[other] >= 2 {
+            self.set_won(player);
+        } else if self.games == [GAMES_PER_SET; 2] {
+            self.tiebreak = true;
+            self.tiebreak_server = self.server;
+        }
+    }
+
+    fn set_won(&mut self, player: usize) {
+        self.set_scores[self.sets_played] = (self.games[0], self.games[1]);
+        self.sets_played += 1;
+        self.sets[player] += 1;
+        self.games = [0; 2];
+        if self.sets[player] == SETS_TO_WIN {
+            self.winner = Some(player);
+        }
+    }
+
+    /// The player serving the next point.
+    pub fn server(&self) -> usize {
+        if self.tiebreak {
+            let played = self.points[0] + self.points[1];
+            self.tiebreak_server ^ ((played as usize).div_ceil(2) % 2)
+        } else {
+            self.server
+        }
+    }
+
+    pub fn in_tiebreak(&self) -> bool {
+        self.tiebreak
+    }
+
+    pub fn games(&self) -> (u32, u32) {
+        (self.games[0], self.games[1])
+    }
+
+    pub fn sets(&self) -> (u32, u32) {
+        (self.sets[0], self.sets[1])
+    }
+
+    /// Games won by each player in every completed set, in order.
+    pub fn set_scores(&self) -> &[(u32, u32)] {
+        &self.set_scores[..self.sets_played]
+    }
+
+    /// Returns how the match ends, or None if play continues. When time runs out the player
+    /// ahead on sets, then games, then points wins; a dead heat is settled by **rules**.
+    pub fn outcome(&self, rules: &MatchRules, time_up: bool) -> Option<Outcome> {
+        let leader = if let Some(winner) = self.winner {
+            Some(winner)
+        } else if time_up {
+            let standing = |p: usize| (self.sets[p], self.games[p], self.points[p]);
+            match standing(0).cmp(&standing(1)) {
+                core::cmp::Ordering::Greater => Some(0),
+                core::cmp::Ordering::Less => Some(1),
+                core::cmp::Ordering::Equal if rules.golden_goal => return None,
+                core::cmp::Ordering::Equal => return Some(Outcome::Draw),
+            }
+        } else {
+            None
+        };
+        leader.map(|p| if p == 0 { Outcome::Player1 } else { Outcome::Player2 })
+    }
+
+    /// Writes the score of the current game for **player**: 0/15/30/40/AD, or the
+    /// number of points during a tie-break.
+    pub fn write_points(&self, player: usize, line: &mut TextLine) {
+        let (mine, theirs) = (self.points[player], self.points[1 - player]);
+        let _ = if self.tiebreak {
+            write!(line, "{}", mine)
+        } else if mine >= 3 && theirs >= 3 {
+            write!(line, "{}", if mine > theirs { "AD" } else { "40" })
+        } else {
+            write!(line, "{}", POINT_NAMES[mine.min(3) as usize])
+        };
+    }
+
+    /// Writes the completed sets, e.g. "6-4 3-6 7-6".
+    pub fn write_set_scores(&self, line: &mut TextLine) {
+        for (i, (games1, games2)) in self.set_scores().iter().enumerate() {
+            let _ = write!(line, "{}{}-{}", if i > 0 { " " } else { "" }, games1, games2);
+        }
+    }
+
+    /// Draws a one-line scoreboard on **row**, player 1 to the left of center and player 2
+    /// to the right, in **colors**. A '*' marks the server.
+    pub fn render<S: Surface + ?Sized>(&self, screen: &mut S, row: usize, colors: [ColorCode; 2]) {
+        let serving = |p: usize| if self.server() == p { "*" } else { " " };
+        let label = if self.tiebreak { "TB " } else { "" };
+
+        let mut points = TextLine::new();
+        self.write_points(0, &mut points);
+        let mut left = TextLine::new();
+        let _ = write!(left, "{}P1  SETS {}  GAMES {}  {}{:>2}",
+            serving(0), self.sets[0], self.games[0], label, points.as_str());
+        let left_x = (BUFFER_WIDTH / 2 - 2).saturating_sub(left.len());
+        screen.plot_str(left.as_str(), left_x, row, colors[0]);
+
+        let mut right = TextLine::new();
+        points.clear();
+        self.write_points(1, &mut points);
+        let _ = write!(right, "{:<2}  GAMES {}  SETS {}  P2{}", points.as_str(), self.games[1], self.sets[1], serving(1));
+        screen.plot_str(right.as_str(), BUFFER_WIDTH / 2 + 2, row, colors[1]);
+    }
+}
+
+/// Writes a one-line summary of the tennis scoring rules.
+pub fn describe_scoring(line: &mut TextLine) {
+    let _ = write!(line, "Best of {} sets, tie-break at {}-{}", MAX_SETS, GAMES_PER_SET, GAMES_PER_SET);
+}
+
+impl Default for TennisScore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub struct Tennis;
+
+impl Tennis {
+    /// The score of the match **game** is playing.
+    pub fn score(game: &Game) -> &TennisScore {
+        match &game.mode_state {
+            ModeState::Tennis(score) => score,
+            _ => unreachable!("not playing Tennis"),
+        }
+    }
+
+    fn score_mut(game: &mut Game) -> &mut TennisScore {
+        match &mut game.mode_state {
+            ModeState::Tennis(score) => score,
+            _ => unreachable!("not playing Tennis"),
+        }
+    }
+}
+
+impl GameMode for Tennis {
+    fn name(&self) -> &'static str {
+        "Tennis"
+    }
+
+    fn menu_item(&self) -> MenuItem {
+        MenuItem::new("[T]ennis", Some('t'))
+    }
+
+    fn palette(&self) -> Palette {
+        Palette {
+            field: Color::Blue,
+            lines: Color::White,
+            accent: Color::White,
+            net: Color::White,
+            ball: Color::Green,
+            player1: Color::Yellow,
+            player2: Color::Red,
+            player3: Color::White,
+            player4: Color::LightGreen,
+            hud: Color::Yellow,
+        }
+    }
+
+    fn draw_field(&self, screen: &mut dyn Surface, palette: &Palette) {
+        let court_color = ColorCode::new(palette.lines, palette.field);
+    
+        // Draw the outer boundary
+        for y in 0..BUFFER_HEIGHT {
+            screen.plot('|', 0, y, court_color);
+            screen.plot('|', BUFFER_WIDTH - 1, y, court_color);
+        }
+        for x in 0..BUFFER_WIDTH {
+            screen.plot('-', x, 0, court_color);
+            screen.plot('-', x, BUFFER_HEIGHT - 1, court_color);
+
+            screen.plot('-', x, 2, court_color);
+            screen.plot('-', x, BUFFER_HEIGHT - 3, court_color);
+        }
+    
+        // Draw the horizontal line
+        let horizontal_y = BUFFER_HEIGHT / 2;
+        for x in BUFFER_WIDTH / 4..BUFFER_WIDTH * 3 / 4 {
+            screen.plot('-', x, horizontal_y, court_color);
+        }
+    
+        // Draw the vertical lines
+        let vertical_x1 = BUFFER_WIDTH / 4;
+        // let vertical_x2 = BUFFER_WIDTH / 2;
+        let vertical_x3 = BUFFER_WIDTH * 3 / 4;
+    
+        for y in 3..BUFFER_HEIGHT -3 {
+            screen.plot('|', vertical_x1, y, court_color);
+            screen.plot('|', vertical_x3, y, court_color);
+            // screen.plot('|', vertical_x2, y, court_color);
+        }
+
+        for y in 0..BUFFER_HEIGHT {
+            screen.plot('|', BUFFER_WIDTH / 2, y, court_color);
+        }
+    }
+
+    fn max_ball_speed(&self) -> Fixed {
+        Fixed::from_ratio(5, 4)
+    }
+
+    fn new_state(&self) -> ModeState {
+        ModeState::Tennis(TennisScore::new())
+    }
+
+    /// Serves from in front of the server's paddle, toward the other end.
+    fn serve(&self, game: &mut Game, _x_dir: isize) {
+        let server = &game.players[Self::score(game).server()];
+        let x_dir = if server.x < BUFFER_WIDTH / 2 { 1 } else { -1 };
+        let col = (server.x as isize + x_dir) as usize;
+        let row = server.y + PADDLE_HEIGHT / 2;
+        game.ball.reset(col, row, x_dir, 1, self.serve_speed());
+    }
+
+    fn point_won(&self, game: &mut Game, player: usize) {
+        Self::score_mut(game).point_won(player);
+    }
+
+    fn outcome(&self, game: &Game) -> Option<Outcome> {
+        Self::score(game).outcome(&game.rules, game.clock.time_up())
+    }
+
+    fn describe_scoring(&self, _game: &Game, line: &mut TextLine) {
+        describe_scoring(line);
+    }
+
+    fn describe_result(&self, game: &Game, line: &mut TextLine) {
+        Self::score(game).write_set_scores(line);
+    }
+
+    /// Row 1 sits between the court's outer boundary and its top line.
+    fn draw_score(&self, game: &Game, screen: &mut dyn Surface) {
+        let palette = game.palette();
+        let colors = [ColorCode::new(palette.player1, palette.field), ColorCode::new(palette.player2, palette.field)];
+        Self::score(game).render(screen, 1, colors);
+    }
+
+    /// The scoreboard already fills row 1.
+    fn clock_row(&self) -> usize {
+        BUFFER_HEIGHT - 2
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::harness::Simulation;
+
+    #[test]
+    fn tennis_games_sets_and_tiebreaks() {
+        let mut score = TennisScore::new();
+        let mut line = TextLine::new();
+        for player in [0, 0, 0, 1, 1, 1, 1] {
+            score.point_won(player);
+        }
+        score.write_points(1, &mut line);
+        assert_eq!(line.as_str(), "AD");
+        score.point_won(1);
+        assert_eq!(score.games(), (0, 1));
+        assert_eq!(score.server(), 1);
+
+        // Alternate games up to 6-6 and into a tie-break.
+        for game in 0..11 {
+            for _ in 0..4 {
+                score.point_won(game % 2);
+            }
+        }
+        assert_eq!(score.games(), (6, 6));
+        assert!(score.in_tiebreak());
+        let first = score.server();
+        score.point_won(0);
+        assert_ne!(score.server(), first);
+        score.point_won(0);
+        assert_ne!(score.server(), first);
+        score.point_won(0);
+        assert_eq!(score.server(), first);
+        for _ in 0..4 {
+            score.point_won(0);
+        }
+        assert_eq!(score.sets(), (1, 0));
+        assert_eq!(score.set_scores(), &[(7, 6)]);
+        assert_eq!(score.server(), 1 - first);
+    }
+
+    #[test]
+    fn tennis_scoreboard_follows_points() {
+        let mut sim = Simulation::start_match('t', '0');
+        sim.ticks(1);
+        assert!(sim.screen_contains("*P1  SETS 0  GAMES 0   0"));
+        assert!(sim.run_until(1000, |game| game.scores() != (0, 0)));
+        sim.ticks(1);
+        let (score1, _) = sim.game().scores();
+        let expected = if score1 == 1 { "P1  SETS 0  GAMES 0  15" } else { "15  GAMES 0  SETS 0  P2" };
+        assert!(sim.screen_contains(expected));
+    }
+}
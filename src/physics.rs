@@ -0,0 +1,403 @@
+// Ball movement and collision detection.
+//
+// Each tick the ball travels along a straight segment. Rather than testing only the cell it
+// ends up in, step() finds the earliest point along that segment where it meets a wall, a
+// paddle face, a goal net, a brick or an end line, resolves that contact, and continues with whatever
+// motion is left. A fast ball therefore cannot skip over a paddle column. Paddles, like nets,
+// only stop the ball at their front, the face toward midfield; a ball behind one passes by.
+
+use crate::platform::vga_buffer::{BUFFER_WIDTH, BUFFER_HEIGHT};
+use crate::Ball;
+use crate::fixed::Fixed;
+
+// Enough for a ball to reflect off both walls and an end line within a single tick.
+const MAX_CONTACTS_PER_STEP: usize = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Side {
+    Left,
+    Right,
+}
+
+/// The top or bottom edge of the arena.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Edge {
+    Top,
+    Bottom,
+}
+
+/// What the ball ran into during a step, other than walls it bounced off.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Contact {
+    /// The ball reached the front face of the paddle at this index, moving toward the end
+    /// of the field behind it. It is left at the point of contact, still moving toward the
+    /// paddle, for the caller to send back.
+    Paddle(usize),
+    /// The ball crossed an end line inside its goal mouth, or entered a net through its front.
+    Goal(Side),
+    /// The ball bounced off the block with this **id**.
+    Block(usize),
+    /// The ball reached the top or bottom of an arena that is open along that edge.
+    Out(Edge),
+}
+
+/// A paddle occupying **length** cells starting at (**col**, **row**): down the column, or
+/// along the row if it is **horizontal**.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Paddle {
+    pub col: usize,
+    pub row: usize,
+    pub length: usize,
+    pub horizontal: bool,
+}
+
+impl Paddle {
+    // Whether the paddle covers **pos**, a row of a vertical paddle or a column of a horizontal one.
+    fn covers(&self, pos: i32) -> bool {
+        let start = if self.horizontal { self.col } else { self.row } as i32;
+        pos >= start && pos < start + self.length as i32
+    }
+
+    // The edge of the paddle facing midfield, if a ball moving across it with **velocity**
+    // is heading into that face rather than coming from behind the paddle.
+    fn front(&self, velocity: Fixed) -> Option<Fixed> {
+        let (pos, middle) = if self.horizontal { (self.row, BUFFER_HEIGHT / 2) } else { (self.col, BUFFER_WIDTH / 2) };
+        let center = Fixed::from_int(pos as i32);
+        if pos < middle {
+            (velocity < Fixed::ZERO).then_some(center + Fixed::HALF)
+        } else {
+            (velocity > Fixed::ZERO).then_some(center - Fixed::HALF)
+        }
+    }
+}
+
+/// A brick covering columns **left** to **right** of **row**. The ball bounces off any face
+/// of it, and the contact reports its **id**.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Block {
+    pub id: usize,
+    pub left: usize,
+    pub right: usize,
+    pub row: usize,
+}
+
+impl Block {
+    pub const EMPTY: Block = Block { id: 0, left: 0, right: 0, row: 0 };
+}
+
+/// The column at one end of the field. Crossing it between **mouth_top** and
+/// **mouth_bottom** (inclusive) scores; anywhere else the ball bounces back.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EndLine {
+    pub col: usize,
+    pub mouth_top: usize,
+    pub mouth_bottom: usize,
+}
+
+impl EndLine {
+    /// An end line that scores along its whole length.
+    pub fn open(col: usize) -> Self {
+        Self { col, mouth_top: 0, mouth_bottom: BUFFER_HEIGHT - 1 }
+    }
+
+    /// An end line the ball always bounces off, like the boards of a rink.
+    pub fn closed(col: usize) -> Self {
+        Self { col, mouth_top: BUFFER_HEIGHT, mouth_bottom: 0 }
+    }
+
+    fn in_mouth(&self, row: i32) -> bool {
+        row >= self.mouth_top as i32 && row <= self.mouth_bottom as i32
+    }
+}
+
+/// A goal net standing inside the arena, covering columns **front** to **back** and rows
+/// **top** to **bottom**. The ball scores by entering through the face on the **front**
+/// column and bounces off the back and sides, so it can travel behind the net.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Net {
+    /// The end of the arena this net defends.
+    pub side: Side,
+    pub front: usize,
+    pub back: usize,
+    pub top: usize,
+    pub bottom: usize,
+}
+
+impl Net {
+    fn columns(&self) -> (i32, i32) {
+        (self.front.min(self.back) as i32, self.front.max(self.back) as i32)
+    }
+
+    fn covers(&self, row: i32) -> bool {
+        row >= self.top as i32 && row <= self.bottom as i32
+    }
+
+    // Whether a ball moving with **x_velocity** would meet the front face rather than the back.
+    fn faces(&self, x_velocity: Fixed) -> bool {
+        (x_velocity < Fixed::ZERO) == (self.side == Side::Left)
+    }
+}
+
+/// The walls and end lines that bound the ball.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Arena {
+    pub top: usize,
+    pub bottom: usize,
+    pub left: EndLine,
+    pub right: EndLine,
+    pub nets: [Option<Net>; 2],
+    /// Whether the ball leaves play at the top wall instead of bouncing off it.
+    pub open_ceiling: bool,
+    /// Whether the ball leaves play at the bottom wall instead of bouncing off it.
+    pub open_floor: bool,
+}
+
+impl Arena {
+    /// The whole screen, scoring anywhere along the left and right edges.
+    pub fn full_screen() -> Self {
+        Self {
+            top: 0,
+            bottom: BUFFER_HEIGHT - 1,
+            left: EndLine::open(0),
+            right: EndLine::open(BUFFER_WIDTH - 1),
+            nets: [None; 2],
+            open_ceiling: false,
+            open_floor: false,
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Hit {
+    // Reverses the vertical direction: the top and bottom walls, and the sides of a net.
+    Wall,
+    // Reverses the horizontal direction: the back of a net.
+    Rebound,
+    Paddle(usize),
+    End(Side),
+    Net(Side),
+    // The id of a block, and whether the ball met one of its ends rather than its top or bottom.
+    Block(usize, bool),
+    Out(Edge),
+}
+
+/// Moves **ball** through one tick of motion within **arena**, bouncing it off walls and
+/// closed parts of the end lines, and stopping at the first paddle, goal or block it reaches.
+pub fn step(ball: &mut Ball, arena: &Arena, paddles: &[Paddle], blocks: &[Block]) -> Option<Contact> {
+    ball.apply_spin();
+    let mut x = ball.x();
+    let mut y = ball.y();
+    let mut x_velocity = ball.x_velocity();
+    let mut y_velocity = ball.y_velocity();
+    let mut remaining = Fixed::ONE;
+    let mut contact = None;
+
+    for _ in 0..MAX_CONTACTS_PER_STEP {
+        let mut first: Option<(Fixed, Hit)> = None;
+
+        if y_velocity < Fixed::ZERO {
+            let t = (Fixed::from_int(arena.top as i32) - y) / y_velocity;
+            earliest(&mut first, t, remaining, if arena.open_ceiling { Hit::Out(Edge::Top) } else { Hit::Wall });
+        } else if y_velocity > Fixed::ZERO {
+            let t = (Fixed::from_int(arena.bottom as i32) - y) / y_velocity;
+            earliest(&mut first, t, remaining, if arena.open_floor { Hit::Out(Edge::Bottom) } else { Hit::Wall });
+        }
+
+        for (i, paddle) in paddles.iter().enumerate() {
+            // Only motion across the paddle, toward its front, can reach its face.
+            let (pos, velocity, along, along_velocity) = if paddle.horizontal {
+                (y, y_velocity, x, x_velocity)
+            } else {
+                (x, x_velocity, y, y_velocity)
+            };
+            if let Some(face) = paddle.front(velocity) {
+                let t = (face - pos) / velocity;
+                if paddle.covers((along + along_velocity * t).round()) {
+                    earliest(&mut first, t, remaining, Hit::Paddle(i));
+                }
+            }
+        }
+
+        for block in blocks {
+            let (left, right, row) = (block.left as i32, block.right as i32, block.row as i32);
+            if x_velocity != Fixed::ZERO {
+                let face = if x_velocity > Fixed::ZERO {
+                    Fixed::from_int(left) - Fixed::HALF
+                } else {
+                    Fixed::from_int(right) + Fixed::HALF
+                };
+                let t = (face - x) / x_velocity;
+                if (y + y_velocity * t).round() == row {
+                    earliest(&mut first, t, remaining, Hit::Block(block.id, true));
+                }
+            }
+            if y_velocity != Fixed::ZERO {
+                let face = Fixed::from_int(row) - Fixed::HALF * y_velocity.signum();
+                let t = (face - y) / y_velocity;
+                let col = (x + x_velocity * t).round();
+                if col >= left && col <= right {
+                    earliest(&mut first, t, remaining, Hit::Block(block.id, false));
+                }
+            }
+        }
+
+        if x_velocity != Fixed::ZERO {
+            let (end, side) = if x_velocity < Fixed::ZERO {
+                (arena.left, Side::Left)
+            } else {
+                (arena.right, Side::Right)
+            };
+            let t = (Fixed::from_int(end.col as i32) - x) / x_velocity;
+            earliest(&mut first, t, remaining, Hit::End(side));
+        }
+
+        for net in arena.nets.iter().flatten() {
+            let (left, right) = net.columns();
+            if x_velocity != Fixed::ZERO {
+                let face = if x_velocity > Fixed::ZERO {
+                    Fixed::from_int(left) - Fixed::HALF
+                } else {
+                    Fixed::from_int(right) + Fixed::HALF
+                };
+                let t = (face - x) / x_velocity;
+                if net.covers((y + y_velocity * t).round()) {
+                    let hit = if net.faces(x_velocity) { Hit::Net(net.side) } else { Hit::Rebound };
+                    earliest(&mut first, t, remaining, hit);
+                }
+            }
+            if y_velocity != Fixed::ZERO {
+                let face = if y_velocity > Fixed::ZERO {
+                    Fixed::from_int(net.top as i32) - Fixed::HALF
+                } else {
+                    Fixed::from_int(net.bottom as i32) + Fixed::HALF
+                };
+                let t = (face - y) / y_velocity;
+                let col = (x + x_velocity * t).round();
+                if col >= left && col <= right {
+                    earliest(&mut first, t, remaining, Hit::Wall);
+                }
+            }
+        }
+
+        match first {
+            None => {
+                x += x_velocity * remaining;
+                y += y_velocity * remaining;
+                break;
+            }
+            Some((t, hit)) => {
+                x += x_velocity * t;
+                y += y_velocity * t;
+                remaining -= t;
+                match hit {
+                    Hit::Wall => y_velocity = -y_velocity,
+                    Hit::Rebound => x_velocity = -x_velocity,
+                    Hit::Paddle(i) => {
+                        contact = Some(Contact::Paddle(i));
+                        break;
+                    }
+                    Hit::End(side) => {
+                        let end = if side == Side::Left { arena.left } else { arena.right };
+                        if end.in_mouth(y.round()) {
+                            contact = Some(Contact::Goal(side));
+                            break;
+                        }
+                        x_velocity = -x_velocity;
+                    }
+                    Hit::Net(side) => {
+                        contact = Some(Contact::Goal(side));
+                        break;
+                    }
+                    Hit::Block(id, end) => {
+                        if end {
+                            x_velocity = -x_velocity;
+                        } else {
+                            y_velocity = -y_velocity;
+                        }
+                        contact = Some(Contact::Block(id));
+                        break;
+                    }
+                    Hit::Out(edge) => {
+                        contact = Some(Contact::Out(edge));
+                        break;
+                    }
+                }
+            }
+        }
+    }
+
+    let x = x.clamp(Fixed::from_int(arena.left.col as i32), Fixed::from_int(arena.right.col as i32));
+    let y = y.clamp(Fixed::from_int(arena.top as i32), Fixed::from_int(arena.bottom as i32));
+    ball.place(x, y, x_velocity, y_velocity);
+    contact
+}
+
+// Keeps **hit** if it happens within this step and sooner than the best found so far.
+fn earliest(first: &mut Option<(Fixed, Hit)>, t: Fixed, remaining: Fixed, hit: Hit) {
+    if t >= Fixed::ZERO && t <= remaining && first.is_none_or(|(best, _)| t < best) {
+        *first = Some((t, hit));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ball(x: Fixed, y: Fixed, x_velocity: Fixed, y_velocity: Fixed) -> Ball {
+        let mut ball = Ball::new(0, 0, 1, 1, Fixed::ONE);
+        ball.place(x, y, x_velocity, y_velocity);
+        ball
+    }
+
+    #[test]
+    fn fast_ball_stops_at_the_paddle_face() {
+        // At 2.5 cells per tick the ball would jump from column 70 to 72.5, over column 72.
+        let speed = Fixed::from_ratio(5, 2);
+        let paddle = Paddle { col: 72, row: 10, length: 5, horizontal: false };
+        let mut fast = ball(Fixed::from_int(70), Fixed::from_int(12), speed, Fixed::ZERO);
+        assert_eq!(step(&mut fast, &Arena::full_screen(), &[paddle], &[]), Some(Contact::Paddle(0)));
+        assert!(fast.x() > Fixed::from_int(71) && fast.x() <= Fixed::from_ratio(143, 2));
+        assert_eq!(fast.x_velocity(), speed);
+
+        // The same ball passes a paddle covering other rows.
+        let mut past = ball(Fixed::from_int(70), Fixed::from_int(20), speed, Fixed::ZERO);
+        assert_eq!(step(&mut past, &Arena::full_screen(), &[paddle], &[]), None);
+        assert_eq!(past.x(), Fixed::from_ratio(145, 2));
+    }
+
+    #[test]
+    fn ball_behind_a_paddle_passes_it() {
+        // Between a closed end line and the back of a paddle, the ball bounces off the end
+        // and leaves through the paddle's column instead of being trapped.
+        let mut arena = Arena::full_screen();
+        arena.left = EndLine::closed(0);
+        let paddle = Paddle { col: 2, row: 10, length: 5, horizontal: false };
+        let mut behind = ball(Fixed::ONE, Fixed::from_int(12), -Fixed::ONE, Fixed::ZERO);
+        for _ in 0..4 {
+            assert_eq!(step(&mut behind, &arena, &[paddle], &[]), None);
+        }
+        assert_eq!(behind.x(), Fixed::from_int(3));
+        assert_eq!(behind.x_velocity(), Fixed::ONE);
+
+        // Coming back from midfield, it meets the front face.
+        let mut returning = ball(Fixed::from_int(4), Fixed::from_int(12), -Fixed::ONE, Fixed::ZERO);
+        step(&mut returning, &arena, &[paddle], &[]);
+        assert_eq!(step(&mut returning, &arena, &[paddle], &[]), Some(Contact::Paddle(0)));
+    }
+
+    #[test]
+    fn ball_bounces_off_a_wall_partway_through_a_step() {
+        let two = Fixed::from_int(2);
+        let mut bouncing = ball(Fixed::from_int(40), Fixed::from_int(23), Fixed::ONE, two);
+        assert_eq!(step(&mut bouncing, &Arena::full_screen(), &[], &[]), None);
+        assert_eq!((bouncing.x(), bouncing.y()), (Fixed::from_int(41), Fixed::from_int(23)));
+        assert_eq!(bouncing.y_velocity(), -two);
+    }
+
+    #[test]
+    fn ball_is_kept_inside_the_arena() {
+        let mut outside = ball(Fixed::from_int(-3), Fixed::from_int(30), Fixed::ZERO, Fixed::ZERO);
+        assert_eq!(step(&mut outside, &Arena::full_screen(), &[], &[]), None);
+        assert_eq!((outside.x(), outside.y()), (Fixed::ZERO, Fixed::from_int(BUFFER_HEIGHT as i32 - 1)));
+    }
+}
@@ -0,0 +1,127 @@
+// Crash reports.
+//
+// When the kernel stops on an exception it cannot recover from, or panics, it replaces the
+// screen with a report of what went wrong and sends the same report to SERIAL1, so it can be
+// read from a terminal when QEMU runs without a display. The report ends with whatever the
+// HandlerTable's crash_info handler has to say about the game. After a panic the player can
+// press R to reboot through the keyboard controller.
+
+use core::fmt::{self, Write};
+use x86_64::instructions::port::{Port, PortReadOnly};
+use super::serial::SERIAL1;
+use super::vga_buffer::{self, BUFFER_WIDTH, BUFFER_HEIGHT, Color, ColorCode, WRITER};
+use super::interrupts;
+
+const REPORT_COLOR: ColorCode = ColorCode::new(Color::White, Color::Blue);
+const TITLE_COLOR: ColorCode = ColorCode::new(Color::Yellow, Color::Blue);
+
+/// Keyboard controller ports. Bit 0 of the status register is set while a byte waits at the
+/// data port, and bit 1 while the controller has yet to take the last byte written to it.
+const KEYBOARD_DATA_PORT: u16 = 0x60;
+const KEYBOARD_STATUS_PORT: u16 = 0x64;
+const OUTPUT_FULL: u8 = 0x01;
+const INPUT_FULL: u8 = 0x02;
+/// Written to the status port, this pulses the CPU's reset line.
+const RESET_COMMAND: u8 = 0xFE;
+/// Scancode set 1 code for pressing R.
+const R_PRESSED: u8 = 0x13;
+
+/// Writes a crash report to the VGA screen and the serial port at once. Text that runs off
+/// the bottom of the screen still goes to the serial port.
+pub struct CrashReport {
+    col: usize,
+    row: usize,
+    color: ColorCode,
+}
+
+impl CrashReport {
+    /// Disables interrupts, takes over the screen and serial port, and starts a report
+    /// headed **title**.
+    pub fn begin(title: &str) -> Self {
+        x86_64::instructions::interrupts::disable();
+        // Nothing else runs once a report begins, so the locks may be taken even if the
+        // code that crashed was holding them.
+        unsafe {
+            WRITER.force_unlock();
+            SERIAL1.force_unlock();
+        }
+        for row in 0..BUFFER_HEIGHT {
+            vga_buffer::clear_row(row, Color::Blue);
+        }
+        let mut report = Self { col: 0, row: 0, color: TITLE_COLOR };
+        let _ = writeln!(report, "*** {} ***", title);
+        report.color = REPORT_COLOR;
+        let _ = writeln!(report);
+        report
+    }
+
+    /// Adds what the game was doing, from the crash_info handler, if one is set and the
+    /// handler table is not locked by the code that crashed.
+    pub fn game_state(&mut self) {
+        if let Some(handlers) = interrupts::handlers() {
+            let _ = writeln!(self);
+            handlers.handle_crash_info(self);
+        }
+    }
+
+    /// Ends the report with a prompt, then waits for R to be pressed and reboots.
+    pub fn reboot_on_r(mut self) -> ! {
+        let _ = writeln!(self);
+        self.color = TITLE_COLOR;
+        let _ = writeln!(self, "Press R to reboot.");
+        // Interrupts are off, so the keyboard is read by polling the controller.
+        let mut status: PortReadOnly<u8> = PortReadOnly::new(KEYBOARD_STATUS_PORT);
+        let mut data: PortReadOnly<u8> = PortReadOnly::new(KEYBOARD_DATA_PORT);
+        unsafe {
+            loop {
+                if status.read() & OUTPUT_FULL != 0 && data.read() == R_PRESSED {
+                    break;
+                }
+            }
+        }
+        reboot()
+    }
+
+    fn new_line(&mut self) {
+        self.col = 0;
+        self.row += 1;
+    }
+}
+
+/// Resets the machine by asking the keyboard controller to pulse the CPU's reset line.
+pub fn reboot() -> ! {
+    let mut status: Port<u8> = Port::new(KEYBOARD_STATUS_PORT);
+    unsafe {
+        while status.read() & INPUT_FULL != 0 {}
+        status.write(RESET_COMMAND);
+    }
+    // The reset takes a moment to arrive.
+    super::hlt_loop()
+}
+
+impl Write for CrashReport {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        for c in s.chars() {
+            if c == '\n' {
+                self.new_line();
+                continue;
+            }
+            if self.col == BUFFER_WIDTH {
+                self.new_line();
+            }
+            if self.row < BUFFER_HEIGHT {
+                let c = if vga_buffer::is_drawable(c) { c } else { '?' };
+                vga_buffer::plot(c, self.col, self.row, self.color);
+            }
+            self.col += 1;
+        }
+        let mut serial = SERIAL1.lock();
+        for (i, line) in s.split('\n').enumerate() {
+            if i > 0 {
+                serial.write_str("\r\n")?;
+            }
+            serial.write_str(line)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,288 @@
+use core::fmt::Write;
+use x86_64::instructions::port::Port;
+use x86_64::registers::control::Cr2;
+use x86_64::structures::idt::{HandlerFunc, InterruptDescriptorTable, InterruptStackFrame, PageFaultErrorCode};
+use crate::println;
+use lazy_static::lazy_static;
+use pic8259::ChainedPics;
+use spin::Mutex;
+use super::crash::CrashReport;
+use super::{gdt, hlt_loop, HandlerTable};
+
+// This code is largely Copyright (c) 2019 Philipp Oppermann.
+// Gabriel Ferrer added:
+// - HANDLERS variable.
+// - Use of HANDLERS in init_idt, timer_interrupt_handler, keyboard_interrupt_handler
+//
+// The keyboard handler passes on each raw KeyEvent as well as the key it decodes to.
+// Every one of the 16 legacy IRQ lines has an IDT entry. Lines without a handler are masked
+// at the PICs, and the rest are passed to the HandlerTable.
+//
+// Every CPU exception but a breakpoint stops the kernel with a crash report.
+
+lazy_static! {
+    static ref HANDLERS: Mutex<Option<HandlerTable>> = Mutex::new(None);
+}
+
+lazy_static! {
+    static ref IDT: InterruptDescriptorTable = {
+        let mut idt = InterruptDescriptorTable::new();
+        idt.divide_error.set_handler_fn(exception_handler::<0>);
+        idt.debug.set_handler_fn(exception_handler::<1>);
+        idt.non_maskable_interrupt.set_handler_fn(exception_handler::<2>);
+        idt.breakpoint.set_handler_fn(breakpoint_handler);
+        idt.overflow.set_handler_fn(exception_handler::<4>);
+        idt.bound_range_exceeded.set_handler_fn(exception_handler::<5>);
+        idt.invalid_opcode.set_handler_fn(exception_handler::<6>);
+        idt.device_not_available.set_handler_fn(exception_handler::<7>);
+        unsafe {
+            idt.double_fault.set_handler_fn(double_fault_handler)
+                .set_stack_index(gdt::DOUBLE_FAULT_IST_INDEX);
+        }
+        idt.invalid_tss.set_handler_fn(error_code_handler::<10>);
+        idt.segment_not_present.set_handler_fn(error_code_handler::<11>);
+        idt.stack_segment_fault.set_handler_fn(error_code_handler::<12>);
+        idt.general_protection_fault.set_handler_fn(error_code_handler::<13>);
+        idt.page_fault.set_handler_fn(page_fault_handler);
+        idt.x87_floating_point.set_handler_fn(exception_handler::<16>);
+        idt.alignment_check.set_handler_fn(error_code_handler::<17>);
+        idt.machine_check.set_handler_fn(machine_check_handler);
+        idt.simd_floating_point.set_handler_fn(exception_handler::<19>);
+        idt.virtualization.set_handler_fn(exception_handler::<20>);
+        idt.cp_protection_exception.set_handler_fn(error_code_handler::<21>);
+        idt.hv_injection_exception.set_handler_fn(exception_handler::<28>);
+        idt.vmm_communication_exception.set_handler_fn(error_code_handler::<29>);
+        idt.security_exception.set_handler_fn(error_code_handler::<30>);
+        for (line, handler) in IRQ_ENTRIES.iter().enumerate() {
+            idt[usize::from(PIC_1_OFFSET) + line].set_handler_fn(*handler);
+        }
+        idt
+    };
+}
+
+/// Initializes the interrupt table with the given interrupt handlers.
+pub fn init_idt(handlers: HandlerTable) {
+    *(HANDLERS.lock()) = Some(handlers);
+    IDT.load();
+}
+
+/// The installed handlers, unless there are none yet or the lock on them is held, as it is
+/// while an interrupt handler runs.
+pub fn handlers() -> Option<HandlerTable> {
+    HANDLERS.try_lock().and_then(|handlers| *handlers)
+}
+
+/// The names of the CPU exceptions, by vector.
+pub const EXCEPTION_NAMES: [&str; 32] = [
+    "DIVIDE ERROR", "DEBUG", "NON-MASKABLE INTERRUPT", "BREAKPOINT",
+    "OVERFLOW", "BOUND RANGE EXCEEDED", "INVALID OPCODE", "DEVICE NOT AVAILABLE",
+    "DOUBLE FAULT", "COPROCESSOR SEGMENT OVERRUN", "INVALID TSS", "SEGMENT NOT PRESENT",
+    "STACK-SEGMENT FAULT", "GENERAL PROTECTION FAULT", "PAGE FAULT", "RESERVED",
+    "X87 FLOATING-POINT EXCEPTION", "ALIGNMENT CHECK", "MACHINE CHECK", "SIMD FLOATING-POINT EXCEPTION",
+    "VIRTUALIZATION EXCEPTION", "CONTROL PROTECTION EXCEPTION", "RESERVED", "RESERVED",
+    "RESERVED", "RESERVED", "RESERVED", "RESERVED",
+    "HYPERVISOR INJECTION EXCEPTION", "VMM COMMUNICATION EXCEPTION", "SECURITY EXCEPTION", "RESERVED",
+];
+
+/// Shows a crash report for exception **vector**, with its **error_code** if it pushes one,
+/// and halts.
+fn crash(vector: u8, error_code: Option<u64>, stack_frame: &InterruptStackFrame) -> ! {
+    let mut report = CrashReport::begin("CPU EXCEPTION");
+    let _ = writeln!(report, "{} (vector {})", EXCEPTION_NAMES[vector as usize], vector);
+    if let Some(code) = error_code {
+        let _ = writeln!(report, "Error code: {:#x}", code);
+    }
+    if let (14, Some(code)) = (vector, error_code) {
+        let _ = writeln!(report, "{:?}", PageFaultErrorCode::from_bits_truncate(code));
+        let _ = writeln!(report, "Accessed address (CR2): {:?}", Cr2::read());
+    }
+    let _ = writeln!(report, "{:#?}", stack_frame);
+    report.game_state();
+    let _ = writeln!(report, "\nSystem halted.");
+    hlt_loop()
+}
+
+extern "x86-interrupt" fn exception_handler<const VECTOR: u8>(stack_frame: InterruptStackFrame) {
+    crash(VECTOR, None, &stack_frame);
+}
+
+extern "x86-interrupt" fn error_code_handler<const VECTOR: u8>(stack_frame: InterruptStackFrame, error_code: u64) {
+    crash(VECTOR, Some(error_code), &stack_frame);
+}
+
+extern "x86-interrupt" fn page_fault_handler(stack_frame: InterruptStackFrame, error_code: PageFaultErrorCode) {
+    crash(14, Some(error_code.bits()), &stack_frame);
+}
+
+extern "x86-interrupt" fn machine_check_handler(stack_frame: InterruptStackFrame) -> ! {
+    crash(18, None, &stack_frame);
+}
+
+extern "x86-interrupt" fn breakpoint_handler(
+    stack_frame: InterruptStackFrame)
+{
+    println!("EXCEPTION: BREAKPOINT\n{:#?}", stack_frame);
+}
+
+extern "x86-interrupt" fn double_fault_handler(
+    stack_frame: InterruptStackFrame, error_code: u64) -> !
+{
+    crash(8, Some(error_code), &stack_frame);
+}
+
+const PIC_1_OFFSET: u8 = 32;
+const PIC_2_OFFSET: u8 = PIC_1_OFFSET + 8;
+
+pub static PICS: Mutex<ChainedPics> =
+    Mutex::new(unsafe { ChainedPics::new(PIC_1_OFFSET, PIC_2_OFFSET) });
+
+/// The number of legacy IRQ lines, 0 to 7 on the primary PIC and 8 to 15 on the secondary.
+pub const IRQ_LINES: usize = 16;
+
+/// Legacy IRQ line numbers, as wired on a PC.
+pub mod irq {
+    pub const TIMER: u8 = 0;
+    pub const KEYBOARD: u8 = 1;
+    /// The secondary PIC signals the primary on this line, so it has no device of its own.
+    pub const CASCADE: u8 = 2;
+    pub const COM2: u8 = 3;
+    pub const COM1: u8 = 4;
+    pub const LPT2: u8 = 5;
+    pub const FLOPPY: u8 = 6;
+    pub const LPT1: u8 = 7;
+    pub const RTC: u8 = 8;
+    pub const MOUSE: u8 = 12;
+    pub const FPU: u8 = 13;
+    pub const PRIMARY_ATA: u8 = 14;
+    pub const SECONDARY_ATA: u8 = 15;
+}
+
+// Port commands for the PICs, beyond what ChainedPics offers.
+const PIC_1_COMMAND: u16 = 0x20;
+const PIC_2_COMMAND: u16 = 0xA0;
+const READ_ISR: u8 = 0x0B;
+const END_OF_INTERRUPT: u8 = 0x20;
+
+const IRQ_ENTRIES: [HandlerFunc; IRQ_LINES] = [
+    irq_handler::<0>, keyboard_interrupt_handler, irq_handler::<2>, irq_handler::<3>,
+    irq_handler::<4>, irq_handler::<5>, irq_handler::<6>, irq_handler::<7>,
+    irq_handler::<8>, irq_handler::<9>, irq_handler::<10>, irq_handler::<11>,
+    irq_handler::<12>, irq_handler::<13>, irq_handler::<14>, irq_handler::<15>,
+];
+
+/// Starts both PICs, leaving unmasked only the IRQ lines **handlers** has a handler for,
+/// and the cascade line if any of those are on the secondary PIC.
+pub fn init_pics(handlers: &HandlerTable) {
+    let [primary, secondary] = irq_masks(handlers);
+    unsafe {
+        let mut pics = PICS.lock();
+        pics.initialize();
+        pics.write_masks(primary, secondary);
+    }
+}
+
+/// The masks for the primary and secondary PIC that let through just the IRQ lines
+/// **handlers** has a handler for. A set bit masks its line.
+pub fn irq_masks(handlers: &HandlerTable) -> [u8; 2] {
+    let mut masked = u16::MAX;
+    for line in 0..IRQ_LINES as u8 {
+        if handlers.handles_irq(line) {
+            masked &= !(1 << line);
+        }
+    }
+    if masked >> 8 != 0xFF {
+        masked &= !(1 << irq::CASCADE);
+    }
+    [masked as u8, (masked >> 8) as u8]
+}
+
+/// Acknowledges IRQ **line**. Lines on the secondary PIC arrive at the primary through the
+/// cascade line, so both PICs need an end-of-interrupt for them.
+fn end_of_interrupt(line: u8) {
+    unsafe {
+        PICS.lock().notify_end_of_interrupt(PIC_1_OFFSET + line);
+    }
+}
+
+/// Whether IRQ **line** is spurious. A PIC raises IRQ 7 or 15 by itself when an interrupt
+/// goes away before the CPU takes it, and such an interrupt is not in service.
+fn is_spurious(line: u8) -> bool {
+    if line != irq::LPT1 && line != irq::SECONDARY_ATA {
+        return false;
+    }
+    let _pics = PICS.lock();
+    let mut command = Port::<u8>::new(if line < 8 { PIC_1_COMMAND } else { PIC_2_COMMAND });
+    let in_service = unsafe {
+        command.write(READ_ISR);
+        command.read()
+    };
+    in_service & 0x80 == 0
+}
+
+/// Runs the handler for IRQ **LINE**, unless the interrupt is spurious. A spurious one gets
+/// no handler call and no end-of-interrupt from the PIC that raised it, though for IRQ 15
+/// the primary still gets one for the cascade.
+extern "x86-interrupt" fn irq_handler<const LINE: u8>(_stack_frame: InterruptStackFrame) {
+    if is_spurious(LINE) {
+        if LINE == irq::SECONDARY_ATA {
+            let _pics = PICS.lock();
+            unsafe { Port::<u8>::new(PIC_1_COMMAND).write(END_OF_INTERRUPT) };
+        }
+        return;
+    }
+    let h = &*HANDLERS.lock();
+    if let Some(handler) = h {
+        handler.handle_irq(LINE);
+    }
+    end_of_interrupt(LINE);
+}
+
+extern "x86-interrupt" fn keyboard_interrupt_handler(_stack_frame: InterruptStackFrame) {
+    use pc_keyboard::{layouts, HandleControl, Keyboard, ScancodeSet1};
+
+    lazy_static! {
+        static ref KEYBOARD: Mutex<Keyboard<layouts::Us104Key, ScancodeSet1>> =
+            Mutex::new(Keyboard::new(layouts::Us104Key, ScancodeSet1,
+                HandleControl::Ignore)
+            );
+    }
+
+    let mut keyboard = KEYBOARD.lock();
+    let mut port = Port::new(0x60);
+
+    let scancode: u8 = unsafe { port.read() };
+    if let Ok(Some(key_event)) = keyboard.add_byte(scancode) {
+        let h = &*HANDLERS.lock();
+        if let Some(handler) = h {
+            handler.handle_raw_keyboard(key_event.clone());
+        }
+        if let Some(key) = keyboard.process_keyevent(key_event) {
+            if let Some(handler) = h {
+                handler.handle_keyboard(key);
+            }
+        }
+    }
+
+    end_of_interrupt(irq::KEYBOARD);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pc_keyboard::DecodedKey;
+
+    #[test]
+    fn only_handled_irq_lines_are_unmasked() {
+        fn nothing() {}
+        fn key(_key: DecodedKey) {}
+
+        let handlers = HandlerTable::new().timer(nothing).keyboard(key);
+        assert_eq!(irq_masks(&handlers), [0b1111_1100, 0xFF]);
+
+        // A line on the secondary PIC also needs the cascade line open on the primary
+        let handlers = handlers.irq(irq::COM1, nothing).irq(irq::RTC, nothing).irq(irq::MOUSE, nothing);
+        assert_eq!(irq_masks(&handlers), [0b1110_1000, 0b1110_1110]);
+        assert!(handlers.handles_irq(irq::MOUSE));
+        assert!(!handlers.handles_irq(irq::CASCADE));
+    }
+}
@@ -0,0 +1,188 @@
+// Bare-metal platform layer.
+//
+// Everything that talks to the hardware lives here: the VGA text buffer, the serial port,
+// the GDT and the interrupt table. The game itself never touches these directly; the binary
+// plugs its handlers into a HandlerTable, which sets up the hardware, routes the timer,
+// keyboard and any other device interrupts to those handlers and then hands the CPU to the
+// main loop. A panic anywhere ends in a crash report, with the option to reboot, except
+// under the QEMU test runner in testing, where it fails the test run.
+//
+// This layer began as the pluggable_interrupt_os crate by Gabriel Ferrer, itself built on
+// Philipp Oppermann's "Writing an OS in Rust".
+
+pub mod crash;
+pub mod gdt;
+pub mod interrupts;
+pub mod serial;
+pub mod testing;
+pub mod vga_buffer;
+
+use core::fmt::Write;
+use pc_keyboard::{DecodedKey, KeyEvent};
+use interrupts::{irq, IRQ_LINES};
+
+/// The functions the kernel calls in response to interrupts, and at startup.
+///
+/// Build one with **new()** and the setters, then call **start()**. Each handler is
+/// optional; without a **cpu_loop** the CPU halts between interrupts. IRQ lines without a
+/// handler stay masked.
+#[derive(Clone, Copy)]
+pub struct HandlerTable {
+    // Handlers by IRQ line. The keyboard line is handled by keyboard and raw_keyboard.
+    irqs: [Option<fn()>; IRQ_LINES],
+    keyboard: Option<fn(DecodedKey)>,
+    raw_keyboard: Option<fn(KeyEvent)>,
+    startup: Option<fn()>,
+    cpu_loop: fn() -> !,
+    crash_info: Option<fn(&mut dyn Write)>,
+}
+
+impl HandlerTable {
+    pub fn new() -> Self {
+        Self { irqs: [None; IRQ_LINES], keyboard: None, raw_keyboard: None, startup: None, cpu_loop: hlt_loop, crash_info: None }
+    }
+
+    /// Called on every timer interrupt, about 18.2 times a second.
+    pub fn timer(self, timer_handler: fn()) -> Self {
+        self.irq(irq::TIMER, timer_handler)
+    }
+
+    /// Called on every interrupt from IRQ **line**, e.g. **irq::COM1**, **irq::RTC** or
+    /// **irq::MOUSE**. The handler must clear the interrupt at the device, such as by reading
+    /// the byte waiting at the serial port, or the device will not interrupt again.
+    ///
+    /// Panics for the keyboard line, which has its own setters, and the cascade line, which
+    /// carries the secondary PIC's interrupts.
+    pub fn irq(mut self, line: u8, handler: fn()) -> Self {
+        assert!((line as usize) < IRQ_LINES, "no IRQ line {}", line);
+        assert!(line != irq::KEYBOARD && line != irq::CASCADE, "IRQ {} cannot take a handler", line);
+        self.irqs[line as usize] = Some(handler);
+        self
+    }
+
+    /// Called with each key press, decoded through the US keyboard layout.
+    pub fn keyboard(mut self, keyboard_handler: fn(DecodedKey)) -> Self {
+        self.keyboard = Some(keyboard_handler);
+        self
+    }
+
+    /// Called with every key going up or down, before it is decoded. Unlike **keyboard**,
+    /// this reports releases, so held keys can be tracked.
+    pub fn raw_keyboard(mut self, raw_keyboard_handler: fn(KeyEvent)) -> Self {
+        self.raw_keyboard = Some(raw_keyboard_handler);
+        self
+    }
+
+    /// Called once, after the hardware is set up and before interrupts are enabled.
+    pub fn startup(mut self, startup_handler: fn()) -> Self {
+        self.startup = Some(startup_handler);
+        self
+    }
+
+    /// Runs once interrupts are enabled, and never returns.
+    pub fn cpu_loop(mut self, cpu_loop: fn() -> !) -> Self {
+        self.cpu_loop = cpu_loop;
+        self
+    }
+
+    /// Called while writing a crash report, to add what the game was doing. It may run in
+    /// the middle of anything, so it should only try for locks rather than wait on them.
+    pub fn crash_info(mut self, crash_info_handler: fn(&mut dyn Write)) -> Self {
+        self.crash_info = Some(crash_info_handler);
+        self
+    }
+
+    /// Loads the GDT and interrupt table and starts the interrupt controllers, leaving
+    /// interrupts disabled. **start()** does this itself; tests that need the hardware set up
+    /// without handing over the CPU call it instead, once.
+    pub fn init(self) {
+        gdt::init();
+        interrupts::init_idt(self);
+        interrupts::init_pics(&self);
+    }
+
+    /// Sets up the hardware with **init()** and runs the startup handler, then enables
+    /// interrupts and enters the cpu loop.
+    pub fn start(self) -> ! {
+        self.init();
+        if let Some(startup) = self.startup {
+            startup();
+        }
+        x86_64::instructions::interrupts::enable();
+        (self.cpu_loop)()
+    }
+
+    /// Whether interrupts from IRQ **line** have somewhere to go.
+    pub fn handles_irq(&self, line: u8) -> bool {
+        if line == irq::KEYBOARD {
+            self.keyboard.is_some() || self.raw_keyboard.is_some()
+        } else {
+            self.irqs.get(line as usize).is_some_and(|handler| handler.is_some())
+        }
+    }
+
+    pub fn handle_irq(&self, line: u8) {
+        if let Some(handler) = self.irqs[line as usize] {
+            handler();
+        }
+    }
+
+    pub fn handle_keyboard(&self, key: DecodedKey) {
+        if let Some(keyboard) = self.keyboard {
+            keyboard(key);
+        }
+    }
+
+    pub fn handle_raw_keyboard(&self, event: KeyEvent) {
+        if let Some(raw_keyboard) = self.raw_keyboard {
+            raw_keyboard(event);
+        }
+    }
+
+    pub fn handle_crash_info(&self, out: &mut dyn Write) {
+        if let Some(crash_info) = self.crash_info {
+            crash_info(out);
+        }
+    }
+}
+
+impl Default for HandlerTable {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Reports where and why the kernel panicked, and what the game was doing, on the screen and
+/// SERIAL1, then waits for R to reboot.
+#[cfg(not(test))]
+#[panic_handler]
+fn panic(info: &core::panic::PanicInfo) -> ! {
+    use core::sync::atomic::{AtomicBool, Ordering};
+    // Set once a panic begins, so that a panic while reporting it does not start over.
+    static PANICKING: AtomicBool = AtomicBool::new(false);
+    if PANICKING.swap(true, Ordering::SeqCst) {
+        hlt_loop();
+    }
+    if testing::running() {
+        testing::fail(info);
+    }
+    let mut report = crash::CrashReport::begin("KERNEL PANIC");
+    match info.location() {
+        Some(location) => {
+            let _ = writeln!(report, "Panicked at {}:{}:{}", location.file(), location.line(), location.column());
+        }
+        None => {
+            let _ = writeln!(report, "Panicked at an unknown location");
+        }
+    }
+    let _ = writeln!(report, "{}", info.message());
+    report.game_state();
+    report.reboot_on_r()
+}
+
+/// Halts the CPU until the next interrupt, forever.
+pub fn hlt_loop() -> ! {
+    loop {
+        x86_64::instructions::hlt();
+    }
+}
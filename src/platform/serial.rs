@@ -0,0 +1,56 @@
+// Code in this file is largely Copyright (c) 2019 Philipp Oppermann.
+//
+// Added for reading the serial port:
+// - try_receive()
+
+use uart_16550::SerialPort;
+use spin::Mutex;
+use lazy_static::lazy_static;
+use x86_64::instructions::port::PortReadOnly;
+
+const COM1_PORT: u16 = 0x3F8;
+/// The line status register. Bit 0 is set while a received byte waits at the data port.
+const LINE_STATUS_PORT: u16 = COM1_PORT + 5;
+const DATA_READY: u8 = 0x01;
+
+lazy_static! {
+    pub static ref SERIAL1: Mutex<SerialPort> = {
+        let mut serial_port = unsafe { SerialPort::new(COM1_PORT) };
+        serial_port.init();
+        Mutex::new(serial_port)
+    };
+}
+
+/// Takes the next byte received by SERIAL1, if one is waiting. Receiving uses registers that
+/// sending does not, so this skips the lock on SERIAL1 and may be called from the COM1
+/// interrupt handler while the main loop is sending.
+pub fn try_receive() -> Option<u8> {
+    let mut status: PortReadOnly<u8> = PortReadOnly::new(LINE_STATUS_PORT);
+    let mut data: PortReadOnly<u8> = PortReadOnly::new(COM1_PORT);
+    unsafe {
+        (status.read() & DATA_READY != 0).then(|| data.read())
+    }
+}
+
+#[doc(hidden)]
+pub fn _print(args: ::core::fmt::Arguments) {
+    use core::fmt::Write;
+    SERIAL1.lock().write_fmt(args).expect("Printing to serial failed");
+}
+
+/// Prints to the host through the serial interface.
+#[macro_export]
+macro_rules! serial_print {
+    ($($arg:tt)*) => {
+        $crate::platform::serial::_print(format_args!($($arg)*));
+    };
+}
+
+/// Prints to the host through the serial interface, appending a newline.
+#[macro_export]
+macro_rules! serial_println {
+    () => ($crate::serial_print!("\n"));
+    ($fmt:expr) => ($crate::serial_print!(concat!($fmt, "\n")));
+    ($fmt:expr, $($arg:tt)*) => ($crate::serial_print!(
+        concat!($fmt, "\n"), $($arg)*));
+}
\ No newline at end of file
@@ -0,0 +1,97 @@
+// Tests that run on the bare-metal target, inside QEMU.
+//
+// The integration tests under tests/ are kernels of their own, built with the
+// custom_test_frameworks feature and test_runner() as their runner. Each test reports its
+// name and result over the serial port, and the runner ends the run by writing to QEMU's
+// isa-debug-exit device, so the exit code of QEMU is the result. Cargo.toml passes QEMU the
+// device, sends the serial port to stdout and turns off the display, so
+//
+//     cargo test --test '*'
+//
+// runs them headless through bootimage; plain `cargo test` does the same, as the library and
+// the kernel binary opt out of the standard test harness. The unit tests and the Simulation
+// runs in harness are host tests, run separately with `cargo test-host`, an alias in
+// .cargo/config.toml.
+//
+// An integration test crate begins with
+//
+//     #![no_std]
+//     #![no_main]
+//     #![feature(custom_test_frameworks)]
+//     #![test_runner(BareMetalGame::platform::testing::test_runner)]
+//     #![reexport_test_harness_main = "test_main"]
+//
+// and has a _start() that calls test_main().
+//
+// Adapted from Philipp Oppermann's "Testing" post in "Writing an OS in Rust".
+
+use core::panic::PanicInfo;
+use core::sync::atomic::{AtomicBool, Ordering};
+use x86_64::instructions::port::Port;
+use crate::{serial_print, serial_println};
+
+/// The I/O port of the isa-debug-exit device, as set by iobase in Cargo.toml.
+const ISA_DEBUG_EXIT_PORT: u16 = 0xF4;
+
+// Set once test_runner() starts, so that a panic fails the run instead of showing a crash
+// report and waiting for a reboot.
+static RUNNING: AtomicBool = AtomicBool::new(false);
+
+/// Values written to the isa-debug-exit device. QEMU exits with (value << 1) | 1, so
+/// Success leaves it with 33, which Cargo.toml names as the success code.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u32)]
+pub enum QemuExitCode {
+    Success = 0x10,
+    Failed = 0x11,
+}
+
+/// Exits QEMU with the given **exit_code**.
+pub fn exit_qemu(exit_code: QemuExitCode) -> ! {
+    let mut port: Port<u32> = Port::new(ISA_DEBUG_EXIT_PORT);
+    unsafe {
+        port.write(exit_code as u32);
+    }
+    // Only reached when running without the isa-debug-exit device.
+    super::hlt_loop()
+}
+
+/// A test that reports its own name and result over the serial port.
+pub trait Testable {
+    fn run(&self);
+}
+
+impl<T: Fn()> Testable for T {
+    fn run(&self) {
+        serial_print!("{}...\t", core::any::type_name::<T>());
+        self();
+        serial_println!("[ok]");
+    }
+}
+
+/// Runs each of **tests** in turn and exits QEMU. A failing test panics, which ends the run
+/// there through **fail()**.
+pub fn test_runner(tests: &[&dyn Testable]) {
+    RUNNING.store(true, Ordering::SeqCst);
+    serial_println!("Running {} tests", tests.len());
+    for test in tests {
+        test.run();
+    }
+    exit_qemu(QemuExitCode::Success);
+}
+
+/// Whether **test_runner()** is running.
+pub fn running() -> bool {
+    RUNNING.load(Ordering::SeqCst)
+}
+
+/// Reports a failed test and exits QEMU. The panic handler calls this while tests run.
+pub fn fail(info: &PanicInfo) -> ! {
+    // The test may have panicked while printing.
+    unsafe {
+        super::serial::SERIAL1.force_unlock();
+    }
+    serial_println!("[failed]\n");
+    serial_println!("Error: {}\n", info);
+    exit_qemu(QemuExitCode::Failed)
+}
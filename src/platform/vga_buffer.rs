@@ -67,7 +67,7 @@ pub struct ColorCode(u8);
 
 #[allow(dead_code)]
 impl ColorCode {
-    pub fn new(foreground: Color, background: Color) -> ColorCode {
+    pub const fn new(foreground: Color, background: Color) -> ColorCode {
         ColorCode((background as u8) << 4 | (foreground as u8))
     }
 
@@ -167,10 +167,7 @@ impl Writer {
 
 #[allow(dead_code)]
 pub fn is_drawable(c: char) -> bool {
-    match c as u8 {
-        MIN_DRAWABLE..=MAX_DRAWABLE => true,
-        _ => false
-    }
+    matches!(c as u8, MIN_DRAWABLE..=MAX_DRAWABLE)
 }
 
 impl fmt::Write for Writer {
@@ -182,7 +179,7 @@ impl fmt::Write for Writer {
 
 #[macro_export]
 macro_rules! print {
-    ($($arg:tt)*) => ($crate::vga_buffer::_print(format_args!($($arg)*)));
+    ($($arg:tt)*) => ($crate::platform::vga_buffer::_print(format_args!($($arg)*)));
 }
 
 #[macro_export]
@@ -224,10 +221,8 @@ pub fn clear_screen() {
 /// If the string exceeds the width of the buffer, it will be truncated.
 /// An illegal row will **panic**.
 pub fn plot_str(s: &str, col: usize, row: usize, color: ColorCode) -> usize {
-    use crate::serial_println;
     let end = BUFFER_WIDTH.min(col + s.len());
     for (c, chr) in (col..end).zip(s.chars()) {
-        serial_println!("Plotting {} ({},{})", chr, c, row);
         plot(chr, c, row, color);
     }
     end % BUFFER_WIDTH
@@ -283,7 +278,7 @@ pub fn num_str_len(num: isize) -> usize {
 /// It will **panic** if an illegal row is given.
 pub fn plot_num_right_justified(total_space: usize, num: isize, col: usize, row: usize, color: ColorCode) -> usize {
     let space_needed = num_str_len(num);
-    let leading_spaces = if space_needed < total_space {total_space - space_needed} else {0};
+    let leading_spaces = total_space.saturating_sub(space_needed);
     if leading_spaces > 0 {
         clear(leading_spaces, col, row, ColorCode::new(color.background(), color.background()));
     }
@@ -313,8 +308,8 @@ pub fn plot_num(num: isize, col: usize, row: usize, color: ColorCode) -> usize {
             num /= 10;
             c += 1;
         }
-        for i in 0..c {
-            plot(buffer[i], col + c - i - 1, row, color);
+        for (i, digit) in buffer[..c].iter().enumerate() {
+            plot(*digit, col + c - i - 1, row, color);
         }
         (col + c) % BUFFER_WIDTH
     }
@@ -0,0 +1,106 @@
+// Match rules: how many points win, by what margin, and what happens when time runs out.
+
+use core::fmt::Write;
+use crate::display::TextLine;
+
+pub const MAX_TARGET_SCORE: u32 = 21;
+pub const MAX_WIN_BY: u32 = 3;
+/// Time limits offered by the settings screen, in seconds.
+pub const TIME_LIMITS: [Option<u32>; 6] = [None, Some(60), Some(120), Some(180), Some(300), Some(600)];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    Player1,
+    Player2,
+    Player3,
+    Player4,
+    Draw,
+}
+
+impl Outcome {
+    /// A win for the player in **seat**, 0 being player 1.
+    pub fn win_for(seat: usize) -> Self {
+        match seat {
+            0 => Outcome::Player1,
+            1 => Outcome::Player2,
+            2 => Outcome::Player3,
+            _ => Outcome::Player4,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MatchRules {
+    /// Points needed to win.
+    pub target_score: u32,
+    /// How far ahead the winner must be once they reach **target_score**.
+    pub win_by: u32,
+    /// Length of the match in seconds. None leaves it to the game mode, which may still play
+    /// timed halves or periods.
+    pub time_limit: Option<u32>,
+    /// Whether a match tied when time runs out continues until the next point.
+    /// Otherwise it ends in a draw.
+    pub golden_goal: bool,
+}
+
+impl MatchRules {
+    /// Returns how the match ends with these scores, or None if play continues.
+    pub fn outcome(&self, score1: u32, score2: u32, time_up: bool) -> Option<Outcome> {
+        let leader = if score1 > score2 { Outcome::Player1 } else { Outcome::Player2 };
+        let (high, low) = (score1.max(score2), score1.min(score2));
+        if high >= self.target_score && high - low >= self.win_by {
+            Some(leader)
+        } else if time_up {
+            if score1 != score2 {
+                Some(leader)
+            } else if self.golden_goal {
+                None
+            } else {
+                Some(Outcome::Draw)
+            }
+        } else {
+            None
+        }
+    }
+
+    pub fn adjust_target_score(&mut self, delta: i32) {
+        self.target_score = (self.target_score as i32 + delta).clamp(1, MAX_TARGET_SCORE as i32) as u32;
+    }
+
+    pub fn adjust_win_by(&mut self, delta: i32) {
+        self.win_by = (self.win_by as i32 + delta).clamp(1, MAX_WIN_BY as i32) as u32;
+    }
+
+    /// Moves to the next longer (positive **delta**) or shorter time limit in TIME_LIMITS.
+    pub fn adjust_time_limit(&mut self, delta: i32) {
+        let current = TIME_LIMITS.iter().position(|t| *t == self.time_limit).unwrap_or(0) as i32;
+        let next = (current + delta).clamp(0, TIME_LIMITS.len() as i32 - 1) as usize;
+        self.time_limit = TIME_LIMITS[next];
+    }
+
+    /// Writes a one-line summary of the scoring rules, e.g. "First to 7 points wins!".
+    pub fn describe_scoring(&self, line: &mut TextLine) {
+        let _ = if self.win_by > 1 {
+            write!(line, "First to {} points, win by {}", self.target_score, self.win_by)
+        } else {
+            write!(line, "First to {} points wins!", self.target_score)
+        };
+    }
+
+    /// Writes a one-line summary of the time limit and tie-break.
+    pub fn describe_timing(&self, line: &mut TextLine) {
+        let _ = match self.time_limit {
+            None => write!(line, "No time limit"),
+            Some(seconds) => {
+                let tie_break = if self.golden_goal { "golden goal" } else { "draw if tied" };
+                write!(line, "Time limit {}:{:02}, {}", seconds / 60, seconds % 60, tie_break)
+            }
+        };
+    }
+}
+
+impl Default for MatchRules {
+    fn default() -> Self {
+        Self { target_score: 7, win_by: 1, time_limit: None, golden_goal: true }
+    }
+}
@@ -0,0 +1,183 @@
+// Color themes.
+//
+// Every color the game draws with comes from the current Theme: the menus use its menu
+// colors, and play uses its Palette. The classic theme plays each sport in its own colors;
+// the others use one palette for every sport, chosen to stay readable: high-contrast puts
+// bright colors on black, deuteranopia-safe avoids telling anything apart by red against
+// green, and monochrome draws everything in grays.
+
+use crate::platform::vga_buffer::{ColorCode, Color};
+
+/// The colors a match is played in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Palette {
+    /// Background of the whole playing screen.
+    pub field: Color,
+    /// Field markings.
+    pub lines: Color,
+    /// Markings that stand out from the rest, such as hockey's red lines, and messages
+    /// about play such as a face-off.
+    pub accent: Color,
+    /// Goal nets.
+    pub net: Color,
+    pub ball: Color,
+    pub player1: Color,
+    pub player2: Color,
+    pub player3: Color,
+    pub player4: Color,
+    /// The match clock and scoreboard captions drawn over the field.
+    pub hud: Color,
+}
+
+impl Palette {
+    /// The paddle color for the player in **seat**, 0 being player 1.
+    pub fn player(&self, seat: usize) -> Color {
+        [self.player1, self.player2, self.player3, self.player4][seat.min(3)]
+    }
+}
+
+/// The colors of the menu screens and the pause menu.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MenuColors {
+    pub background: Color,
+    pub text: Color,
+    pub title: Color,
+    /// The selected item is drawn in the background color on this color. It must be one of
+    /// the first eight colors: real VGA hardware blinks text on the bright ones instead.
+    pub highlight: Color,
+}
+
+impl MenuColors {
+    pub fn text(&self) -> ColorCode {
+        ColorCode::new(self.text, self.background)
+    }
+
+    pub fn title(&self) -> ColorCode {
+        ColorCode::new(self.title, self.background)
+    }
+
+    pub fn selected(&self) -> ColorCode {
+        ColorCode::new(self.background, self.highlight)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Theme {
+    pub name: &'static str,
+    /// The palette for play, or None to play each sport in its own colors.
+    pub palette: Option<Palette>,
+    pub menu: MenuColors,
+}
+
+const CLASSIC_MENU: MenuColors = MenuColors {
+    background: Color::Black,
+    text: Color::White,
+    title: Color::Yellow,
+    highlight: Color::LightGray,
+};
+
+/// The built-in themes, in the order the settings screen offers them. The first is the default.
+pub const THEMES: [Theme; 4] = [
+    Theme { name: "Classic", palette: None, menu: CLASSIC_MENU },
+    Theme {
+        name: "High contrast",
+        palette: Some(Palette {
+            field: Color::Black,
+            lines: Color::LightGray,
+            accent: Color::White,
+            net: Color::LightGray,
+            ball: Color::White,
+            player1: Color::LightCyan,
+            player2: Color::Yellow,
+            player3: Color::Pink,
+            player4: Color::LightRed,
+            hud: Color::LightGreen,
+        }),
+        menu: MenuColors {
+            background: Color::Black,
+            text: Color::White,
+            title: Color::Yellow,
+            highlight: Color::LightGray,
+        },
+    },
+    Theme {
+        name: "Deuteranopia-safe",
+        palette: Some(Palette {
+            field: Color::Blue,
+            lines: Color::LightGray,
+            accent: Color::Yellow,
+            net: Color::LightGray,
+            ball: Color::White,
+            player1: Color::LightCyan,
+            player2: Color::Yellow,
+            player3: Color::White,
+            player4: Color::Pink,
+            hud: Color::White,
+        }),
+        menu: MenuColors {
+            background: Color::Black,
+            text: Color::White,
+            title: Color::LightCyan,
+            highlight: Color::Brown,
+        },
+    },
+    Theme {
+        name: "Monochrome",
+        palette: Some(Palette {
+            field: Color::Black,
+            lines: Color::DarkGray,
+            accent: Color::LightGray,
+            net: Color::DarkGray,
+            ball: Color::White,
+            player1: Color::White,
+            player2: Color::White,
+            player3: Color::White,
+            player4: Color::White,
+            hud: Color::LightGray,
+        }),
+        menu: MenuColors {
+            background: Color::Black,
+            text: Color::LightGray,
+            title: Color::White,
+            highlight: Color::LightGray,
+        },
+    },
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pc_keyboard::KeyCode;
+    use crate::harness::{Simulation, Step};
+    use crate::GameState;
+
+    #[test]
+    fn highlights_do_not_blink() {
+        for theme in THEMES {
+            assert!((theme.menu.highlight as u8) < 8, "{} highlight blinks", theme.name);
+        }
+    }
+
+    #[test]
+    fn theme_picker_recolors_play_and_menus() {
+        let mut sim = Simulation::new();
+        sim.run(&[Step::char('s'), Step::raw(KeyCode::ArrowUp), Step::raw(KeyCode::ArrowRight), Step::Ticks(1)]);
+        assert_eq!(sim.game().theme().name, "High contrast");
+        assert!(sim.screen_contains("High contrast"));
+        let (row, col) = sim.find_text("Match Settings:").unwrap();
+        assert_eq!(sim.screen().cell(col, row).color.foreground(), Color::Yellow);
+
+        sim.run(&[Step::raw(KeyCode::ArrowLeft), Step::raw(KeyCode::ArrowLeft), Step::Ticks(1)]);
+        assert_eq!(sim.game().theme().name, "Monochrome");
+        sim.run(&[Step::raw(KeyCode::ArrowRight), Step::raw(KeyCode::ArrowRight), Step::Ticks(1)]);
+        assert_eq!(sim.game().theme().name, "High contrast");
+
+        sim.run(&[Step::raw(KeyCode::Escape), Step::raw(KeyCode::Enter), Step::char('f'), Step::char('0'), Step::Ticks(2)]);
+        assert_eq!(sim.game().state(), GameState::Playing);
+        let player2 = sim.game().player2();
+        let paddle = sim.screen().cell(player2.x, player2.y);
+        assert_eq!(paddle.ch, '#');
+        assert_eq!(paddle.color, ColorCode::new(Color::Yellow, Color::Black));
+        assert_eq!(sim.screen().cell(1, 1).color.background(), Color::Black);
+    }
+}
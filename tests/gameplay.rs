@@ -0,0 +1,122 @@
+// Plays a few seconds of a scripted match on the bare-metal target, driven by the real timer
+// and drawn to the VGA buffer the way main.rs does it.
+
+#![no_std]
+#![no_main]
+#![feature(custom_test_frameworks)]
+#![test_runner(BareMetalGame::platform::testing::test_runner)]
+#![reexport_test_harness_main = "test_main"]
+
+use crossbeam::atomic::AtomicCell;
+use pc_keyboard::KeyCode;
+use x86_64::instructions::{hlt, interrupts};
+use BareMetalGame::display::DoubleBuffer;
+use BareMetalGame::harness::Step;
+use BareMetalGame::platform::vga_buffer::{self, BUFFER_HEIGHT, BUFFER_WIDTH};
+use BareMetalGame::platform::{hlt_loop, HandlerTable};
+use BareMetalGame::{Game, GameState, TICKS_PER_SECOND};
+
+static TICKS: AtomicCell<usize> = AtomicCell::new(0);
+
+fn tick() {
+    TICKS.fetch_add(1);
+}
+
+#[no_mangle]
+pub extern "C" fn _start() -> ! {
+    HandlerTable::new().timer(tick).init();
+    interrupts::enable();
+    test_main();
+    hlt_loop()
+}
+
+/// Waits for the next timer interrupt.
+fn wait_for_tick() {
+    let start = TICKS.load();
+    while TICKS.load() == start {
+        hlt();
+    }
+}
+
+/// Plays **timeline** against **game** the way the Simulation harness does, except that
+/// each tick waits for a real timer interrupt and is flushed to the VGA buffer.
+fn play(game: &mut Game, screen: &mut DoubleBuffer, timeline: &[Step]) {
+    for step in timeline {
+        match step {
+            Step::Key(key) => game.key(*key),
+            Step::Event(event) => game.key_event(event),
+            Step::Ticks(n) => {
+                for _ in 0..*n {
+                    wait_for_tick();
+                    game.tick(screen);
+                    screen.flush();
+                }
+            }
+        }
+    }
+}
+
+#[test_case]
+fn scripted_match_plays_for_three_seconds() {
+    let mut game = Game::new();
+    let mut screen = DoubleBuffer::new();
+    play(
+        &mut game,
+        &mut screen,
+        &[
+            Step::Ticks(1),
+            Step::raw(KeyCode::Enter),
+            Step::Ticks(1),
+            Step::char('f'),
+            Step::Ticks(1),
+            Step::char('0'),
+            Step::Ticks(1),
+        ],
+    );
+    assert_eq!(game.state(), GameState::Playing);
+    let start1 = game.player1().y;
+    let start2 = game.player2().y;
+    let serve = (game.ball().x(), game.ball().y());
+
+    play(
+        &mut game,
+        &mut screen,
+        &[
+            Step::press(KeyCode::W),
+            Step::press(KeyCode::ArrowDown),
+            Step::Ticks(3),
+            Step::release(KeyCode::W),
+            Step::release(KeyCode::ArrowDown),
+            Step::Ticks(3 * TICKS_PER_SECOND - 3),
+        ],
+    );
+    assert_eq!(game.state(), GameState::Playing);
+    assert_eq!(game.player1().y, start1 - 3);
+    assert_eq!(game.player2().y, start2 + 3);
+    assert_ne!((game.ball().x(), game.ball().y()), serve);
+}
+
+#[test_case]
+fn flushed_frames_reach_the_vga_buffer() {
+    let mut game = Game::new();
+    let mut screen = DoubleBuffer::new();
+    play(
+        &mut game,
+        &mut screen,
+        &[
+            Step::Ticks(1),
+            Step::raw(KeyCode::Enter),
+            Step::Ticks(1),
+            Step::char('h'),
+            Step::Ticks(1),
+            Step::char('1'),
+            Step::Ticks(TICKS_PER_SECOND - 3),
+        ],
+    );
+    for row in 0..BUFFER_HEIGHT {
+        for col in 0..BUFFER_WIDTH {
+            let cell = screen.back().cell(col, row);
+            assert_eq!(vga_buffer::peek(col, row), (cell.ch, cell.color));
+        }
+    }
+}
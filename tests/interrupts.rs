@@ -0,0 +1,57 @@
+// Checks the interrupt setup on the bare-metal target: the IDT, the PIC masks and the timer.
+
+#![no_std]
+#![no_main]
+#![feature(custom_test_frameworks)]
+#![test_runner(BareMetalGame::platform::testing::test_runner)]
+#![reexport_test_harness_main = "test_main"]
+
+use crossbeam::atomic::AtomicCell;
+use x86_64::instructions::{hlt, interrupts};
+use BareMetalGame::platform::{hlt_loop, HandlerTable};
+use BareMetalGame::platform::interrupts::{handlers, irq, irq_masks, PICS};
+
+static TICKS: AtomicCell<usize> = AtomicCell::new(0);
+
+fn table() -> HandlerTable {
+    HandlerTable::new().timer(tick)
+}
+
+fn tick() {
+    TICKS.fetch_add(1);
+}
+
+#[no_mangle]
+pub extern "C" fn _start() -> ! {
+    table().init();
+    test_main();
+    hlt_loop()
+}
+
+#[test_case]
+fn handler_table_is_installed() {
+    let installed = handlers().expect("no handler table");
+    assert!(installed.handles_irq(irq::TIMER));
+    assert!(!installed.handles_irq(irq::KEYBOARD));
+}
+
+#[test_case]
+fn breakpoint_exception_returns() {
+    interrupts::int3();
+}
+
+#[test_case]
+fn only_handled_irq_lines_are_unmasked() {
+    let masks = unsafe { PICS.lock().read_masks() };
+    assert_eq!(masks, irq_masks(&table()));
+}
+
+#[test_case]
+fn timer_interrupts_reach_the_handler() {
+    let start = TICKS.load();
+    interrupts::enable();
+    while TICKS.load() < start + 3 {
+        hlt();
+    }
+    interrupts::disable();
+}
@@ -0,0 +1,62 @@
+// Runs the VGA text buffer writer on the bare-metal target.
+
+#![no_std]
+#![no_main]
+#![feature(custom_test_frameworks)]
+#![test_runner(BareMetalGame::platform::testing::test_runner)]
+#![reexport_test_harness_main = "test_main"]
+
+use BareMetalGame::println;
+use BareMetalGame::platform::hlt_loop;
+use BareMetalGame::platform::vga_buffer::{self, BUFFER_WIDTH, BUFFER_HEIGHT, ColorCode, Color};
+
+#[no_mangle]
+pub extern "C" fn _start() -> ! {
+    test_main();
+    hlt_loop()
+}
+
+#[test_case]
+fn println_does_not_panic() {
+    println!("println_does_not_panic output");
+}
+
+#[test_case]
+fn println_scrolls_past_the_bottom_of_the_screen() {
+    for i in 0..200 {
+        println!("line {}", i);
+    }
+    assert_eq!(vga_buffer::peek(0, BUFFER_HEIGHT - 2).0, 'l');
+}
+
+#[test_case]
+fn println_output_is_on_the_screen() {
+    let s = "Some test string that fits on a single line";
+    println!("{}", s);
+    // The newline scrolls the text up from the bottom row.
+    for (col, c) in s.chars().enumerate() {
+        assert_eq!(vga_buffer::peek(col, BUFFER_HEIGHT - 2).0, c);
+    }
+}
+
+#[test_case]
+fn plotted_cells_read_back_with_their_colors() {
+    let color = ColorCode::new(Color::LightCyan, Color::Magenta);
+    vga_buffer::plot('#', 3, 4, color);
+    vga_buffer::plot_str("corner", BUFFER_WIDTH - 6, 0, color);
+    assert_eq!(vga_buffer::peek(3, 4), ('#', color));
+    assert_eq!(vga_buffer::peek(BUFFER_WIDTH - 1, 0), ('r', color));
+    assert_eq!(color.foreground(), Color::LightCyan);
+    assert_eq!(color.background(), Color::Magenta);
+}
+
+#[test_case]
+fn clear_screen_blanks_every_cell() {
+    vga_buffer::plot('#', 3, 4, ColorCode::new(Color::White, Color::Red));
+    vga_buffer::clear_screen();
+    for row in 0..BUFFER_HEIGHT {
+        for col in 0..BUFFER_WIDTH {
+            assert_eq!(vga_buffer::peek(col, row).0, ' ');
+        }
+    }
+}